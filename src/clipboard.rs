@@ -0,0 +1,98 @@
+// src/clipboard.rs
+
+// Copies text to the system clipboard with the OSC 52 terminal escape sequence.
+// The terminal emulator does the copying, so this works over SSH and needs no clipboard libraries.
+// Inside tmux the sequence is wrapped in a passthrough so tmux forwards it to the outer terminal.
+
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, IsTerminal, Write};
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let sequence = osc52_sequence(text, env::var("TMUX").is_ok());
+
+    // Straight to the terminal, so the sequence doesn't end up in a file or pipe when stdout is redirected
+    if let Ok(mut tty) = OpenOptions::new().write(true).open("/dev/tty") {
+        tty.write_all(sequence.as_bytes())?;
+        return tty.flush();
+    }
+
+    // Without one, stdout only works when it is the terminal, a pipe would just get the escape sequence
+    let mut stdout = io::stdout();
+    if !stdout.is_terminal() {
+        return Err(io::Error::other("there is no terminal to copy through"));
+    }
+    stdout.write_all(sequence.as_bytes())?;
+    stdout.flush()
+}
+
+fn osc52_sequence(text: &str, in_tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()));
+
+    if in_tmux {
+        // tmux passthrough: wrap in a DCS string and double every ESC inside it
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+
+        encoded.push(BASE64_ALPHABET[(n >> 18) as usize & 63] as char);
+        encoded.push(BASE64_ALPHABET[(n >> 12) as usize & 63] as char);
+        encoded.push(if chunk.len() > 1 {
+            BASE64_ALPHABET[(n >> 6) as usize & 63] as char
+        } else {
+            '='
+        });
+        encoded.push(if chunk.len() > 2 {
+            BASE64_ALPHABET[n as usize & 63] as char
+        } else {
+            '='
+        });
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_matches_the_rfc_4648_vectors() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foob"), "Zm9vYg==");
+        assert_eq!(base64_encode(b"fooba"), "Zm9vYmE=");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn sequence_outside_tmux() {
+        assert_eq!(osc52_sequence("foo", false), "\x1b]52;c;Zm9v\x07");
+    }
+
+    #[test]
+    fn sequence_inside_tmux_doubles_esc() {
+        assert_eq!(
+            osc52_sequence("foo", true),
+            "\x1bPtmux;\x1b\x1b]52;c;Zm9v\x07\x1b\\"
+        );
+    }
+}
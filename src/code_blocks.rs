@@ -0,0 +1,188 @@
+// src/code_blocks.rs
+
+// Finds the fenced code blocks in a response, numbered from 1 in the order they appear.
+// Fences are detected the same way highlight_code_blocks does it, so the numbers match what's on screen.
+// Blocks are saved as the raw code from the response, never the highlighted text.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct CodeBlock {
+    pub number: usize,
+    // The fence's language tag in lowercase, empty when there is none
+    pub language: String,
+    pub code: String,
+}
+
+pub fn extract_code_blocks(response: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<CodeBlock> = None;
+
+    for line in response.lines() {
+        if line.starts_with("```") {
+            match current.take() {
+                Some(block) => blocks.push(block),
+                None => {
+                    current = Some(CodeBlock {
+                        number: blocks.len() + 1,
+                        language: line.trim_start_matches('`').trim().to_lowercase(),
+                        code: String::new(),
+                    })
+                }
+            }
+        } else if let Some(block) = current.as_mut() {
+            block.code.push_str(line);
+            block.code.push('\n');
+        }
+    }
+
+    // An unclosed block at the end still counts, the highlighter shows it too
+    blocks.extend(current);
+    blocks
+}
+
+// Block N of the response, with an error listing the numbers that exist
+pub fn code_block(response: &str, number: &str) -> Result<CodeBlock, String> {
+    let blocks = extract_code_blocks(response);
+    if blocks.is_empty() {
+        return Err("There are no code blocks in the last response".to_string());
+    }
+
+    number
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| blocks.get(i).cloned())
+        .ok_or_else(|| {
+            format!(
+                "No code block {}, the last response has blocks 1 to {}",
+                number,
+                blocks.len()
+            )
+        })
+}
+
+// The usual file extension for a fence's language tag
+pub fn extension(language: &str) -> &'static str {
+    match language {
+        "python" | "python3" | "py" => "py",
+        "bash" | "sh" | "shell" | "zsh" | "console" => "sh",
+        "rust" | "rs" => "rs",
+        "javascript" | "js" => "js",
+        "typescript" | "ts" => "ts",
+        "csharp" | "cs" | "c#" => "cs",
+        "c" => "c",
+        "cpp" | "c++" => "cpp",
+        "go" => "go",
+        "java" => "java",
+        "ruby" | "rb" => "rb",
+        "swift" => "swift",
+        "html" => "html",
+        "css" => "css",
+        "sql" => "sql",
+        "json" => "json",
+        "yaml" | "yml" => "yml",
+        "toml" => "toml",
+        "markdown" | "md" => "md",
+        _ => "txt",
+    }
+}
+
+// block-2.py, the name used when no file name is given
+pub fn default_file_name(block: &CodeBlock) -> String {
+    format!("block-{}.{}", block.number, extension(&block.language))
+}
+
+// Where `/save code N [path]` writes a block: the default name in the current directory, or inside `path`
+// when it's a directory. A path without an extension gets the one for the block's language.
+pub fn resolve_path(block: &CodeBlock, path: Option<&str>) -> PathBuf {
+    match path {
+        None => PathBuf::from(default_file_name(block)),
+        Some(path) if Path::new(path).is_dir() => Path::new(path).join(default_file_name(block)),
+        Some(path) if Path::new(path).extension().is_none() => {
+            Path::new(path).with_extension(extension(&block.language))
+        }
+        Some(path) => PathBuf::from(path),
+    }
+}
+
+// Writes every block to `dir` under its default name, creating the directory if needed. Files that are
+// already there are left alone unless `overwrite` is set. Returns the paths written and the paths skipped.
+pub fn save_all(
+    blocks: &[CodeBlock],
+    dir: &str,
+    overwrite: bool,
+) -> io::Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    fs::create_dir_all(dir)?;
+
+    let mut written = Vec::new();
+    let mut skipped = Vec::new();
+    for block in blocks {
+        let path = Path::new(dir).join(default_file_name(block));
+        let file = if overwrite {
+            File::create(&path)
+        } else {
+            OpenOptions::new().write(true).create_new(true).open(&path)
+        };
+
+        match file {
+            Ok(mut file) => {
+                file.write_all(block.code.as_bytes())?;
+                written.push(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => skipped.push(path),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok((written, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn numbers_blocks_in_order() {
+        let response = "Intro\n```Python\nprint(1)\n```\ntext\n```\nplain\n```\n```sh\nls";
+        let blocks = extract_code_blocks(response);
+        let summary: Vec<(usize, &str, &str)> = blocks
+            .iter()
+            .map(|block| (block.number, block.language.as_str(), block.code.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "python", "print(1)\n"),
+                (2, "", "plain\n"),
+                (3, "sh", "ls\n")
+            ]
+        );
+    }
+
+    #[test]
+    fn save_all_keeps_existing_files_unless_told_to_overwrite() {
+        let dir = env::temp_dir().join(format!("console_ai_chat-save-test-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let dir_name = dir.to_str().unwrap();
+        let blocks = extract_code_blocks("```py\nnew\n```\n```sh\nls\n```");
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("block-1.py"), "old\n").unwrap();
+
+        let (written, skipped) = save_all(&blocks, dir_name, false).unwrap();
+        assert_eq!(written, vec![dir.join("block-2.sh")]);
+        assert_eq!(skipped, vec![dir.join("block-1.py")]);
+        assert_eq!(fs::read_to_string(dir.join("block-1.py")).unwrap(), "old\n");
+
+        let (written, skipped) = save_all(&blocks, dir_name, true).unwrap();
+        assert_eq!(written.len(), 2);
+        assert!(skipped.is_empty());
+        assert_eq!(fs::read_to_string(dir.join("block-1.py")).unwrap(), "new\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+// src/code_runner.rs
+
+// Runs a python or bash code block from the last response, for `/run N` in a conversation.
+//
+// The code is written to a fresh temporary directory and run there with stdin closed. It's killed after
+// a timeout, and `ulimit` caps its CPU time, memory and the size of files it writes (see `code_runner`
+// in config.json). That keeps runaway loops and huge allocations in check, it is not a sandbox: the code
+// can still read and change anything the user can.
+
+use crate::code_blocks::{code_block, CodeBlock};
+use crate::config::config;
+use crate::conversation::Conversation;
+use crate::danger;
+use crate::process::{run_with_timeout, Captured};
+use crate::prompt::{ask_typed_yes, ask_yes_no};
+use crate::syntax_highlighter::highlight_code;
+use crate::text_formatter::{color_text, danger_badge, format_error};
+use std::env;
+use std::fs;
+use std::process::{self, Command};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Only the end of a long output is kept and sent to the model
+const TAIL_LINES: usize = 60;
+const MAX_OUTPUT_CHARS: usize = 4000;
+
+pub async fn run_code_block(
+    conversation: &mut Conversation,
+    number: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let last = conversation
+        .last_response()
+        .ok_or("There is no response to run code from yet")?;
+    if number.is_empty() {
+        return Err("Usage: /run N, where N is the number of a python or bash code block".into());
+    }
+
+    let block = code_block(&last, number)?;
+    let (interpreter, file_name) = interpreter(&block.language).ok_or_else(|| {
+        format!(
+            "Block {} is {}, only python and bash blocks can be run",
+            block.number,
+            if block.language.is_empty() {
+                "not labeled with a language"
+            } else {
+                &block.language
+            }
+        )
+    })?;
+
+    let limits = &config().code_runner;
+    println!();
+    print!("{}", highlight_code(&block.language, &block.code));
+    println!(
+        "{}",
+        color_text(
+            &format!(
+                "Runs with {} in a temporary directory, for at most {}s and {} MB of memory.",
+                interpreter, limits.timeout_secs, limits.memory_mb
+            ),
+            "yellow"
+        )
+    );
+
+    // Shell code gets the same checks as commands, python is shown as-is
+    let explanations = if file_name.ends_with(".sh") {
+        danger::check(&block.code)
+    } else {
+        Vec::new()
+    };
+    for explanation in &explanations {
+        println!("{} {}", danger_badge(), explanation);
+    }
+    let confirmed = if explanations.is_empty() {
+        ask_yes_no("Run it?")?
+    } else {
+        ask_typed_yes("Type yes to run it anyway:")?
+    };
+    if !confirmed {
+        return Ok(());
+    }
+
+    let captured = run_in_temp_dir(&block, interpreter, file_name)?;
+    if captured.timed_out {
+        eprintln!(
+            "{}",
+            format_error(&format!("Stopped after {}s", limits.timeout_secs))
+        );
+    } else if captured.status.success() {
+        println!("{}", color_text("Finished.", "green"));
+    } else {
+        eprintln!(
+            "{}",
+            format_error(&format!(
+                "Block {} failed ({})",
+                block.number, captured.status
+            ))
+        );
+    }
+
+    if ask_yes_no("Send the output to the model?")? {
+        let response = conversation
+            .send(&result_prompt(&block, &captured, limits.timeout_secs))
+            .await?;
+        conversation.print_response(&response);
+    }
+    Ok(())
+}
+
+// The program to run a block with and the file name to save it as
+fn interpreter(language: &str) -> Option<(&'static str, &'static str)> {
+    match language {
+        "python" | "python3" | "py" => Some(("python3", "main.py")),
+        "bash" | "sh" | "shell" => Some(("bash", "script.sh")),
+        _ => None,
+    }
+}
+
+fn run_in_temp_dir(
+    block: &CodeBlock,
+    interpreter: &str,
+    file_name: &str,
+) -> Result<Captured, Box<dyn std::error::Error>> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let dir = env::temp_dir().join(format!("console_ai_chat-run-{}-{}", process::id(), nanos));
+    // A fresh directory every time, one that already exists isn't ours to run code in
+    fs::create_dir(&dir)?;
+    fs::write(dir.join(file_name), &block.code)?;
+
+    // ulimit takes kilobytes. A limit the system refuses is skipped rather than stopping the run.
+    let limits = &config().code_runner;
+    let script = format!(
+        "ulimit -t {} 2>/dev/null; ulimit -v {} 2>/dev/null; ulimit -f {} 2>/dev/null; exec \"$@\"",
+        limits.timeout_secs,
+        limits.memory_mb * 1024,
+        limits.file_size_mb * 1024
+    );
+    let result = run_with_timeout(
+        Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .arg("sh")
+            .arg(interpreter)
+            .arg(file_name)
+            .current_dir(&dir),
+        true,
+        TAIL_LINES,
+        Duration::from_secs(limits.timeout_secs),
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+    Ok(result?)
+}
+
+fn result_prompt(block: &CodeBlock, captured: &Captured, timeout_secs: u64) -> String {
+    let outcome = if captured.timed_out {
+        format!("was stopped after {} seconds", timeout_secs)
+    } else {
+        format!("finished with {}", captured.status)
+    };
+
+    let mut prompt = format!(
+        "I ran code block {} ({}) from your last answer. It {}.",
+        block.number, block.language, outcome
+    );
+    for (name, output) in [("stdout", &captured.stdout), ("stderr", &captured.stderr)] {
+        let output = output.trim();
+        if output.is_empty() {
+            continue;
+        }
+        let skip = output.chars().count().saturating_sub(MAX_OUTPUT_CHARS);
+        let output: String = output.chars().skip(skip).collect();
+        prompt.push_str(&format!(
+            "\n\nThis is what it printed to {}:\n\n```\n{}\n```",
+            name, output
+        ));
+    }
+    if captured.stdout.trim().is_empty() && captured.stderr.trim().is_empty() {
+        prompt.push_str(" It printed nothing.");
+    }
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn block(language: &str) -> CodeBlock {
+        CodeBlock {
+            number: 2,
+            language: language.to_string(),
+            code: String::new(),
+        }
+    }
+
+    fn captured(code: i32, stdout: &str, stderr: &str, timed_out: bool) -> Captured {
+        Captured {
+            // Wait statuses keep the exit code in the second byte
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            timed_out,
+        }
+    }
+
+    #[test]
+    fn interpreter_for_each_language_tag() {
+        for language in ["python", "python3", "py"] {
+            assert_eq!(interpreter(language), Some(("python3", "main.py")));
+        }
+        for language in ["bash", "sh", "shell"] {
+            assert_eq!(interpreter(language), Some(("bash", "script.sh")));
+        }
+        assert_eq!(interpreter(""), None);
+        assert_eq!(interpreter("rust"), None);
+        assert_eq!(interpreter("zsh"), None);
+    }
+
+    #[test]
+    fn result_prompt_reports_the_exit_status() {
+        let prompt = result_prompt(&block("python"), &captured(0, "hello\n", "", false), 10);
+        assert_eq!(
+            prompt,
+            "I ran code block 2 (python) from your last answer. It finished with exit status: 0.\
+             \n\nThis is what it printed to stdout:\n\n```\nhello\n```"
+        );
+
+        let prompt = result_prompt(&block("bash"), &captured(3, "", "  oops \n", false), 10);
+        assert!(prompt.contains("It finished with exit status: 3."));
+        assert!(prompt.ends_with("This is what it printed to stderr:\n\n```\noops\n```"));
+        assert!(!prompt.contains("stdout"));
+    }
+
+    #[test]
+    fn result_prompt_reports_a_timeout() {
+        let prompt = result_prompt(&block("bash"), &captured(0, "", "", true), 7);
+        assert_eq!(
+            prompt,
+            "I ran code block 2 (bash) from your last answer. It was stopped after 7 seconds. \
+             It printed nothing."
+        );
+    }
+
+    #[test]
+    fn result_prompt_keeps_the_end_of_long_output() {
+        let output = format!("{}{}", "a".repeat(10), "b".repeat(MAX_OUTPUT_CHARS));
+        let prompt = result_prompt(&block("python"), &captured(0, &output, "", false), 10);
+        assert!(prompt.contains(&format!("```\n{}\n```", "b".repeat(MAX_OUTPUT_CHARS))));
+        assert!(!prompt.contains("ab"));
+    }
+}
@@ -0,0 +1,123 @@
+// src/commands/commit.rs
+
+// `commit [-cl|-gpt]` writes a commit message for what's staged. The staged diff goes to the model, which
+// answers in the Conventional Commits format (https://www.conventionalcommits.org). The message opens in
+// $EDITOR for changes and is committed after a final confirmation. Nothing is committed without one.
+
+use super::pick_model;
+use crate::conversation::Conversation;
+use crate::git::{self, git};
+use crate::line_editor::compose_in_editor;
+use crate::prompt::ask_yes_no;
+use crate::session::Session;
+use crate::text_formatter::color_text;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+const SYSTEM_PROMPT: &str = "You write git commit messages that follow the Conventional Commits specification. \
+                             The first line is `type(scope): summary`, where type is one of feat, fix, docs, style, refactor, perf, test, build, ci, chore or revert, \
+                             the scope is optional, and the summary is in the imperative mood, lowercase and under 72 characters. \
+                             Add ! after the type or scope for a breaking change and explain it in a BREAKING CHANGE: footer. \
+                             When the change isn't obvious from the summary, add a body after a blank line saying what changed and why, wrapped at 72 characters. \
+                             Reply with only the commit message, without code fences or commentary.";
+
+// Large diffs are cut, the file list from --stat still shows everything that changed
+const MAX_DIFF_CHARS: usize = 20000;
+
+const EDITOR_NOTE: &str =
+    "\n# Lines starting with '#' are ignored. Save an empty message to cancel the commit.\n";
+
+lazy_static! {
+    static ref CONVENTIONAL: Regex = Regex::new(
+        r"^(feat|fix|docs|style|refactor|perf|test|build|ci|chore|revert)(\([^()]+\))?!?: \S"
+    )
+    .unwrap();
+}
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (model, rest) = pick_model(args);
+    if let Some(other) = rest.first() {
+        return Err(format!("Unknown option '{}'\nUsage: commit [-cl|-gpt]", other).into());
+    }
+
+    let diff = git::diff(&["--staged"])?;
+    if diff.trim().is_empty() {
+        return Err("Nothing is staged, add changes with git add first".into());
+    }
+    let stat = git::diff(&["--staged", "--stat"])?;
+    // Earlier subjects show which scopes the project uses. A new repository has none.
+    let recent = git(&["log", "-10", "--format=%s"]).unwrap_or_default();
+
+    let mut session = Session::new(model.provider(), model.model_name(), None);
+    session.system = Some(SYSTEM_PROMPT.to_string());
+    let mut conversation = Conversation::new(model, session);
+
+    println!("Writing a commit message for:\n{}", stat.trim_end());
+    let response = conversation
+        .send(&build_prompt(&stat, &diff, &recent))
+        .await?;
+
+    let message = compose_in_editor(&format!("{}\n{}", clean_message(&response), EDITOR_NOTE))?;
+    let message: String = message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let message = message.trim();
+    if message.is_empty() {
+        println!("Empty message, nothing was committed.");
+        return Ok(());
+    }
+
+    println!("\n{}\n", color_text(message, "cyan"));
+    if !CONVENTIONAL.is_match(message) {
+        println!(
+            "{}",
+            color_text(
+                "The first line doesn't follow the Conventional Commits format.",
+                "yellow"
+            )
+        );
+    }
+    if !ask_yes_no("Commit with this message?")? {
+        println!("Nothing was committed.");
+        return Ok(());
+    }
+
+    git::commit(&format!("{}\n", message))?;
+    Ok(())
+}
+
+fn build_prompt(stat: &str, diff: &str, recent: &str) -> String {
+    let mut prompt = format!(
+        "Write the commit message for these staged changes.\n\nFiles:\n{}\n",
+        stat.trim_end()
+    );
+    if !recent.trim().is_empty() {
+        prompt.push_str(&format!(
+            "\nRecent commit subjects in this repository:\n{}\n",
+            recent.trim_end()
+        ));
+    }
+
+    let truncated = diff.chars().count() > MAX_DIFF_CHARS;
+    let diff: String = diff.chars().take(MAX_DIFF_CHARS).collect();
+    prompt.push_str(&format!("\nDiff:\n```diff\n{}\n```", diff.trim_end()));
+    if truncated {
+        prompt.push_str("\n\nThe diff was cut short, the file list above has every changed file.");
+    }
+    prompt
+}
+
+// Models sometimes wrap the message in a code fence or quotes anyway
+fn clean_message(response: &str) -> String {
+    response
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .trim_matches('"')
+        .trim()
+        .to_string()
+}
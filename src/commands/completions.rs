@@ -0,0 +1,284 @@
+// src/commands/completions.rs
+
+// `completions <bash|zsh|fish>` prints a completion script for the shell:
+//
+//   eval "$(chat completions bash)"         # ~/.bashrc
+//   source <(chat completions zsh)          # ~/.zshrc, after compinit
+//   chat completions fish | source          # ~/.config/fish/config.fish
+//
+// Fixed words like subcommands and providers are written into the script. Things that change, like saved
+// sessions and custom modifiers from config.json, are looked up when completing with `completions --list <kind>`.
+// The scripts complete both `chat` (the usual alias) and `console_ai_chat`.
+
+use super::shell_init::shell_quote;
+use super::SUBCOMMANDS;
+use crate::models::{from_provider, PROVIDERS};
+use crate::response_types::all_modifiers;
+use crate::session::store::sessions_dir;
+use std::env;
+use std::fs;
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let usage = "Usage: completions <bash|zsh|fish>\n       completions --list <sessions|modes|modifier-flags|models [provider]|providers>";
+
+    match args.first().map(String::as_str) {
+        Some("--list") => {
+            let kind = args.get(1).ok_or(usage)?;
+            for value in list(kind, args.get(2).map(String::as_str))? {
+                println!("{}", value);
+            }
+            Ok(())
+        }
+        Some(shell) => {
+            let template = match shell {
+                "bash" => BASH,
+                "zsh" => ZSH,
+                "fish" => FISH,
+                other => {
+                    return Err(
+                        format!("Unsupported shell '{}', use bash, zsh or fish", other).into(),
+                    )
+                }
+            };
+
+            // The full path of this binary, so lookups work without an alias or PATH entry
+            let program = shell_quote(&env::current_exe()?.display().to_string());
+            let script = template
+                .replace("@PROGRAM@", &program)
+                .replace("@SUBCOMMANDS@", &SUBCOMMANDS.join(" "))
+                .replace("@PROVIDERS@", &PROVIDERS.join(" "));
+            print!("{}", script);
+            Ok(())
+        }
+        None => Err(usage.into()),
+    }
+}
+
+fn list(kind: &str, provider: Option<&str>) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let values = match kind {
+        "sessions" => session_ids(),
+        "modes" => all_modifiers()
+            .iter()
+            .map(|modifier| modifier.name().to_string())
+            .chain(["none".to_string()])
+            .collect(),
+        "modifier-flags" => all_modifiers()
+            .iter()
+            .map(|modifier| modifier.flag().to_string())
+            .collect(),
+        "models" => {
+            let providers: Vec<&str> = match provider {
+                Some(provider) => vec![provider],
+                None => PROVIDERS.to_vec(),
+            };
+            providers
+                .into_iter()
+                .filter_map(from_provider)
+                .flat_map(|model| model.known_models().iter().map(|name| name.to_string()))
+                .collect()
+        }
+        "providers" => PROVIDERS.iter().map(|name| name.to_string()).collect(),
+        other => return Err(format!("Unknown list '{}'", other).into()),
+    };
+    Ok(values)
+}
+
+// Newest first, read from the file names so completion stays fast with many sessions
+fn session_ids() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(sessions_dir()) else {
+        return Vec::new();
+    };
+
+    let mut ids: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.strip_suffix(".json").map(str::to_string)
+        })
+        .collect();
+    ids.sort_by(|a, b| b.cmp(a));
+    ids
+}
+
+const BASH: &str = r#"_console_ai_chat() {
+    local cur=${COMP_WORDS[COMP_CWORD]}
+    local prev=${COMP_WORDS[COMP_CWORD-1]}
+    local first=${COMP_WORDS[1]}
+    local values=""
+
+    case $prev in
+        --model)
+            case $first in
+                -cl) values=$(@PROGRAM@ completions --list models claude) ;;
+                -gpt) values=$(@PROGRAM@ completions --list models gpt) ;;
+                *) values=$(@PROGRAM@ completions --list models) ;;
+            esac
+            COMPREPLY=($(compgen -W "$values" -- "$cur"))
+            return ;;
+        --provider)
+            COMPREPLY=($(compgen -W "@PROVIDERS@" -- "$cur"))
+            return ;;
+        --mode)
+            COMPREPLY=($(compgen -W "$(@PROGRAM@ completions --list modes)" -- "$cur"))
+            return ;;
+        --format)
+            if [[ $first == import ]]; then values="chatgpt claude"; else values="md html"; fi
+            COMPREPLY=($(compgen -W "$values" -- "$cur"))
+            return ;;
+        --emit-script|--save-code|--output|-o|--command|--since|--until|--limit)
+            return ;;
+    esac
+
+    if [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "-cl -gpt --help @SUBCOMMANDS@" -- "$cur"))
+        return
+    fi
+
+    case $first in
+        -cl|-gpt)
+            values="--model --emit-script --emit-json --run --save-code --overwrite --copy $(@PROGRAM@ completions --list modifier-flags)" ;;
+        resume)
+            [[ $COMP_CWORD -eq 2 ]] && values=$(@PROGRAM@ completions --list sessions) ;;
+        export)
+            values="--format --output $(@PROGRAM@ completions --list sessions)" ;;
+        history)
+            if [[ $COMP_CWORD -eq 2 ]]; then values="list search"; else values="--provider --mode --since --until --limit"; fi ;;
+        import)
+            [[ $cur == -* ]] && values="--format" ;;
+        shell-init)
+            if [[ $COMP_CWORD -eq 2 ]]; then values="bash zsh fish"; else values="-cl -gpt"; fi ;;
+        completions)
+            [[ $COMP_CWORD -eq 2 ]] && values="bash zsh fish" ;;
+        explain|run|suggest)
+            [[ " ${COMP_WORDS[*]:2:COMP_CWORD-2} " != *" -- "* ]] && values="-cl -gpt --" ;;
+        fix)
+            values="-cl -gpt --rerun --command" ;;
+        commit)
+            values="-cl -gpt" ;;
+        review)
+            values="-cl -gpt --staged" ;;
+    esac
+
+    COMPREPLY=($(compgen -W "$values" -- "$cur"))
+}
+complete -o default -F _console_ai_chat chat console_ai_chat
+"#;
+
+const ZSH: &str = r#"#compdef chat console_ai_chat
+
+_console_ai_chat() {
+    local first=${words[2]} prev=${words[CURRENT-1]}
+    local provider=""
+    local -a values
+
+    case $first in
+        -cl) provider=claude ;;
+        -gpt) provider=gpt ;;
+    esac
+
+    case $prev in
+        --model)
+            values=(${(f)"$(@PROGRAM@ completions --list models $provider)"})
+            compadd -a values
+            return ;;
+        --provider)
+            compadd @PROVIDERS@
+            return ;;
+        --mode)
+            values=(${(f)"$(@PROGRAM@ completions --list modes)"})
+            compadd -a values
+            return ;;
+        --format)
+            if [[ $first == import ]]; then compadd chatgpt claude; else compadd md html; fi
+            return ;;
+        --emit-script|--save-code|--output|-o)
+            _files
+            return ;;
+        --command|--since|--until|--limit)
+            return ;;
+    esac
+
+    if (( CURRENT == 2 )); then
+        compadd -- -cl -gpt --help @SUBCOMMANDS@
+        return
+    fi
+
+    case $first in
+        -cl|-gpt)
+            values=(${(f)"$(@PROGRAM@ completions --list modifier-flags)"})
+            compadd -- --model --emit-script --emit-json --run --save-code --overwrite --copy $values ;;
+        resume)
+            (( CURRENT == 3 )) && compadd ${(f)"$(@PROGRAM@ completions --list sessions)"} ;;
+        export)
+            compadd -- --format --output ${(f)"$(@PROGRAM@ completions --list sessions)"} ;;
+        history)
+            if (( CURRENT == 3 )); then compadd list search; else compadd -- --provider --mode --since --until --limit; fi ;;
+        import)
+            compadd -- --format
+            _files ;;
+        shell-init)
+            if (( CURRENT == 3 )); then compadd bash zsh fish; else compadd -- -cl -gpt; fi ;;
+        completions)
+            (( CURRENT == 3 )) && compadd bash zsh fish ;;
+        explain|run|suggest)
+            if (( ${words[(I)--]} && ${words[(I)--]} < CURRENT )); then _files; else compadd -- -cl -gpt --; fi ;;
+        fix)
+            compadd -- -cl -gpt --rerun --command ;;
+        commit)
+            compadd -- -cl -gpt ;;
+        review)
+            compadd -- -cl -gpt --staged ;;
+    esac
+}
+
+compdef _console_ai_chat chat console_ai_chat
+"#;
+
+const FISH: &str = r#"function __console_ai_chat_first
+    set -l tokens (commandline -opc)
+    test (count $tokens) -ge 2; and echo $tokens[2]
+end
+
+function __console_ai_chat_nargs
+    count (commandline -opc)
+end
+
+function __console_ai_chat_after
+    set -l tokens (commandline -opc)
+    test "$tokens[-1]" = $argv[1]
+end
+
+function __console_ai_chat_models
+    switch (__console_ai_chat_first)
+        case -cl
+            @PROGRAM@ completions --list models claude
+        case -gpt
+            @PROGRAM@ completions --list models gpt
+        case '*'
+            @PROGRAM@ completions --list models
+    end
+end
+
+for name in chat console_ai_chat
+    complete -c $name -f
+    complete -c $name -n 'test (__console_ai_chat_nargs) -eq 1' -a '-cl -gpt --help @SUBCOMMANDS@'
+
+    complete -c $name -n '__console_ai_chat_after --model' -a '(__console_ai_chat_models)'
+    complete -c $name -n '__console_ai_chat_after --provider' -a '@PROVIDERS@'
+    complete -c $name -n '__console_ai_chat_after --mode' -a '(@PROGRAM@ completions --list modes)'
+    complete -c $name -n '__console_ai_chat_after --format; and test (__console_ai_chat_first) = import' -a 'chatgpt claude'
+    complete -c $name -n '__console_ai_chat_after --format; and test (__console_ai_chat_first) = export' -a 'md html'
+    complete -c $name -n '__console_ai_chat_after --emit-script; or __console_ai_chat_after --save-code; or __console_ai_chat_after --output' -F
+
+    complete -c $name -n 'contains -- (__console_ai_chat_first) -cl -gpt' -a '--model --emit-script --emit-json --run --save-code --overwrite --copy (@PROGRAM@ completions --list modifier-flags)'
+    complete -c $name -n 'test (__console_ai_chat_first) = resume; and test (__console_ai_chat_nargs) -eq 2' -a '(@PROGRAM@ completions --list sessions)'
+    complete -c $name -n 'test (__console_ai_chat_first) = export' -a '--format --output (@PROGRAM@ completions --list sessions)'
+    complete -c $name -n 'test (__console_ai_chat_first) = history; and test (__console_ai_chat_nargs) -eq 2' -a 'list search'
+    complete -c $name -n 'test (__console_ai_chat_first) = history; and test (__console_ai_chat_nargs) -gt 2' -a '--provider --mode --since --until --limit'
+    complete -c $name -n 'test (__console_ai_chat_first) = import' -F -a '--format'
+    complete -c $name -n 'contains -- (__console_ai_chat_first) shell-init completions; and test (__console_ai_chat_nargs) -eq 2' -a 'bash zsh fish'
+    complete -c $name -n 'contains -- (__console_ai_chat_first) explain run suggest fix shell-init commit review; and test (__console_ai_chat_nargs) -ge 2' -a '-cl -gpt'
+    complete -c $name -n 'test (__console_ai_chat_first) = fix' -a '--rerun --command'
+    complete -c $name -n 'test (__console_ai_chat_first) = review' -a '--staged'
+end
+"#;
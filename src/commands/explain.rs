@@ -0,0 +1,636 @@
+// src/commands/explain.rs
+
+// `explain [-cl|-gpt] -- <command line>` is the reverse of command mode: it takes a command and explains
+// each part of it, e.g. `chat explain -- tar -xzvf foo.tgz -C /opt`.
+//
+// The command line is split into its program, subcommands, flags and arguments. The local man page is
+// searched for those flags and the matching lines are sent along with the prompt, so the explanation is
+// based on the version installed here. Without a man page, the program's `--help` output can be used
+// instead, after asking, since that means running it. The answer is drawn as a breakdown under the
+// original command:
+//
+//   tar -xzvf foo.tgz -C /opt
+//   │    ││││ │       │  └─ 8. Directory to change to before extracting
+//   │    ││││ │       └─ 7. Change to a directory first
+//   ...
+
+use super::pick_model;
+use crate::conversation::Conversation;
+use crate::environment::on_path;
+use crate::process::run_with_timeout;
+use crate::prompt::ask_yes_no;
+use crate::session::Session;
+use crate::text_formatter::{color_text, create_header};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+// How much documentation goes into the prompt
+const MAX_DOCS_CHARS: usize = 6000;
+
+// `--help` is only a fallback, a program that ignores it and keeps running is stopped after this
+const HELP_TIMEOUT: Duration = Duration::from_secs(3);
+// More than any help text, MAX_DOCS_CHARS limits what's used of it
+const HELP_LINES: usize = 5000;
+
+lazy_static! {
+    static ref OVERSTRIKE: Regex = Regex::new(r".\x08").unwrap();
+    static ref VALUE_PLACEHOLDER: Regex = Regex::new(r"=|\s[A-Z][A-Z_-]*\b|\s<|\s\[").unwrap();
+    static ref NUMBERED_LINE: Regex = Regex::new(r"^\s*(\d+)[.)]\s+(.*)$").unwrap();
+    static ref SUBCOMMAND: Regex = Regex::new(r"^[a-z][a-z0-9-]*$").unwrap();
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Program,
+    Subcommand,
+    Flag,
+    Value,
+    Argument,
+}
+
+// One part of the command line and the column it starts at in the displayed command
+struct Part {
+    text: String,
+    kind: Kind,
+    column: usize,
+}
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (model, rest) = pick_model(args);
+
+    // A single quoted argument is split into words
+    let words = match rest {
+        [line] => split_words(line),
+        words => words.to_vec(),
+    };
+    if words.is_empty() {
+        return Err(usage().into());
+    }
+
+    let documentation = Documentation::load(&words);
+    let (line, parts) = split_command(&words, &documentation);
+
+    let prompt = build_prompt(&line, &parts, &documentation);
+    let session = Session::new(model.provider(), model.model_name(), None);
+    let mut conversation = Conversation::new(model, session);
+    let response = conversation.send(&prompt).await?;
+
+    println!("\n{}", create_header());
+    print_breakdown(&line, &parts, &response);
+    Ok(())
+}
+
+fn usage() -> String {
+    "Usage: explain [-cl|-gpt] -- <command line>".to_string()
+}
+
+// Splits a command line on whitespace, keeping quoted text together
+pub fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+// Man page or --help text for the program and each of its subcommands
+struct Documentation {
+    text: String,
+}
+
+impl Documentation {
+    fn load(words: &[String]) -> Self {
+        let program = &words[0];
+        let mut help = HelpFallback {
+            program,
+            allowed: None,
+        };
+        let mut text = read_man(program)
+            .or_else(|| help.read(&[]))
+            .unwrap_or_default();
+
+        // Subcommands have their own pages, e.g. git-commit(1) or `cargo build --help`
+        let mut path: Vec<&str> = Vec::new();
+        for word in &words[1..] {
+            if !SUBCOMMAND.is_match(word) || !lists_subcommand(&text, word) {
+                break;
+            }
+            path.push(word);
+            let page = format!("{}-{}", program, path.join("-"));
+            if let Some(docs) = read_man(&page).or_else(|| help.read(&path)) {
+                text.push_str("\n\n");
+                text.push_str(&docs);
+            }
+        }
+
+        Documentation { text }
+    }
+
+    // The option's definition line and the description under it
+    fn excerpt(&self, flag: &str) -> Option<String> {
+        let lines: Vec<&str> = self.text.lines().collect();
+        let start = lines.iter().position(|line| defines(line, flag))?;
+
+        let mut excerpt = vec![lines[start].trim()];
+        for line in &lines[start + 1..] {
+            let trimmed = line.trim();
+            // The next option's definition ends this one
+            if trimmed.is_empty() || trimmed.starts_with('-') {
+                break;
+            }
+            excerpt.push(trimmed);
+            if excerpt.len() >= 6 {
+                break;
+            }
+        }
+        Some(excerpt.join(" "))
+    }
+
+    // Whether the option is documented with a value, e.g. "-C, --directory=DIR" or "-o FILE"
+    fn takes_value(&self, flag: &str) -> bool {
+        self.text
+            .lines()
+            .find(|line| defines(line, flag))
+            .map(|line| option_spec_has_value(line.trim().split("  ").next().unwrap_or(""), flag))
+            .unwrap_or(false)
+    }
+
+    // The first line of the NAME section, e.g. "tar - an archiving utility"
+    fn summary(&self) -> Option<&str> {
+        let mut lines = self.text.lines();
+        lines.find(|line| line.trim() == "NAME")?;
+        lines.next().map(str::trim).filter(|line| !line.is_empty())
+    }
+}
+
+// A definition line starts with options, one of which is `flag`: "-x, --extract, --get"
+fn defines(line: &str, flag: &str) -> bool {
+    let trimmed = line.trim_start();
+    if !trimmed.starts_with('-') {
+        return false;
+    }
+    let spec = trimmed.split("  ").next().unwrap_or("");
+    spec.split([',', ' ', '['])
+        .any(|word| word.split('=').next() == Some(flag))
+}
+
+// Looks for a placeholder after the flag in its definition, e.g. "--file=ARCHIVE" or "-o <file>".
+// Short and long forms share the value, "-f, --file=ARCHIVE" means -f takes one too. Optional values like
+// "--color[=WHEN]" don't take the next word.
+fn option_spec_has_value(spec: &str, flag: &str) -> bool {
+    // The flag as a whole word, -f shouldn't be found inside --file
+    let part_of_word = |c: char| c == '-' || c.is_alphanumeric();
+    let Some(position) = spec.match_indices(flag).map(|(i, _)| i).find(|&i| {
+        !spec[..i].chars().next_back().is_some_and(part_of_word)
+            && !spec[i + flag.len()..]
+                .chars()
+                .next()
+                .is_some_and(part_of_word)
+    }) else {
+        return false;
+    };
+    let after = &spec[position + flag.len()..];
+    if after.starts_with("[=") || spec.contains("[=") {
+        return false;
+    }
+    let next = after.split(',').next().unwrap_or("");
+    VALUE_PLACEHOLDER.is_match(next) || spec.contains('=')
+}
+
+// Subcommands show up at the start of a line in the parent's help, e.g. "   commit   Record changes"
+fn lists_subcommand(text: &str, word: &str) -> bool {
+    text.lines().any(|line| {
+        let trimmed = line.trim_start();
+        trimmed.starts_with(word)
+            && trimmed[word.len()..]
+                .chars()
+                .next()
+                .is_none_or(|c| c.is_whitespace() || c == ',')
+    })
+}
+
+// `man page` with formatting removed
+fn read_man(page: &str) -> Option<String> {
+    let output = Command::new("man")
+        .arg(page)
+        .env("MANPAGER", "cat")
+        .env("MANWIDTH", "100")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    Some(OVERSTRIKE.replace_all(&text, "").into_owned())
+}
+
+// `program [subcommands] --help` for programs without a man page. That runs the program being explained, so
+// it's only offered for commands installed on PATH, never a path like ./install.sh, and only after asking.
+// The answer holds for the rest of the command line.
+struct HelpFallback<'a> {
+    program: &'a str,
+    allowed: Option<bool>,
+}
+
+impl HelpFallback<'_> {
+    fn read(&mut self, subcommands: &[&str]) -> Option<String> {
+        let mut args: Vec<&str> = subcommands.to_vec();
+        args.push("--help");
+
+        let allowed = *self.allowed.get_or_insert_with(|| {
+            !self.program.contains('/')
+                && on_path(self.program)
+                && ask_yes_no(&format!(
+                    "There is no man page for {}. Run `{} {}` to read its options?",
+                    self.program,
+                    self.program,
+                    args.join(" ")
+                ))
+                .unwrap_or(false)
+        });
+        if !allowed {
+            return None;
+        }
+
+        let captured = run_with_timeout(
+            Command::new(self.program).args(&args),
+            false,
+            HELP_LINES,
+            HELP_TIMEOUT,
+        )
+        .ok()
+        .filter(|captured| !captured.timed_out)?;
+
+        // Some programs print their help on stderr
+        let text = captured.stdout + &captured.stderr;
+        Some(text).filter(|text| !text.trim().is_empty())
+    }
+}
+
+// The displayed command line and its parts. Combined short flags like -xzvf are split into one part per letter.
+fn split_command(words: &[String], documentation: &Documentation) -> (String, Vec<Part>) {
+    let mut line = String::new();
+    let mut parts = Vec::new();
+    let mut expect_value = false;
+    let mut in_subcommands = true;
+    let mut after_double_dash = false;
+
+    for (i, word) in words.iter().enumerate() {
+        if i > 0 {
+            line.push(' ');
+        }
+        let column = line.chars().count();
+        let shown = if word.is_empty() || word.contains(char::is_whitespace) {
+            format!("'{}'", word)
+        } else {
+            word.clone()
+        };
+        line.push_str(&shown);
+
+        let kind = if i == 0 {
+            Kind::Program
+        } else if expect_value {
+            expect_value = false;
+            Kind::Value
+        } else if after_double_dash || !word.starts_with('-') || word == "-" {
+            if word == "--" {
+                after_double_dash = true;
+            }
+            if in_subcommands
+                && SUBCOMMAND.is_match(word)
+                && lists_subcommand(&documentation.text, word)
+            {
+                Kind::Subcommand
+            } else {
+                in_subcommands = false;
+                Kind::Argument
+            }
+        } else if word.starts_with("--") {
+            in_subcommands = false;
+            let name = word.split('=').next().unwrap_or(word);
+            expect_value = !word.contains('=') && documentation.takes_value(name);
+            Kind::Flag
+        } else {
+            in_subcommands = false;
+            // -xzvf is -x -z -v -f, the last letter can take the next word as its value
+            let letters: Vec<char> = word.chars().skip(1).collect();
+            if letters.len() > 1 && letters.iter().all(|c| c.is_ascii_alphabetic()) {
+                for (offset, letter) in letters.iter().enumerate() {
+                    parts.push(Part {
+                        text: format!("-{}", letter),
+                        kind: Kind::Flag,
+                        column: column + offset + 1,
+                    });
+                }
+                let last = format!("-{}", letters[letters.len() - 1]);
+                expect_value = documentation.takes_value(&last);
+                continue;
+            }
+            expect_value = letters.len() == 1 && documentation.takes_value(word);
+            Kind::Flag
+        };
+
+        parts.push(Part {
+            text: shown,
+            kind,
+            column,
+        });
+    }
+
+    (line, parts)
+}
+
+fn build_prompt(line: &str, parts: &[Part], documentation: &Documentation) -> String {
+    let mut prompt = format!(
+        "Explain this command line part by part:\n\n    {}\n\nIts parts are:\n",
+        line
+    );
+    for (i, part) in parts.iter().enumerate() {
+        let kind = match part.kind {
+            Kind::Program => "program",
+            Kind::Subcommand => "subcommand",
+            Kind::Flag => "flag",
+            Kind::Value => "value of the flag before it",
+            Kind::Argument => "argument",
+        };
+        prompt.push_str(&format!("{}. `{}` ({})\n", i + 1, part.text, kind));
+    }
+    prompt.push_str(
+        "\nReply with the same numbered list, one short line per part explaining what it does in this command, \
+         then one line starting with \"Summary:\" describing what the whole command does. Nothing else.",
+    );
+
+    // Grounding from the installed documentation
+    let mut docs = String::new();
+    if let Some(summary) = documentation.summary() {
+        docs.push_str(summary);
+        docs.push('\n');
+    }
+    for part in parts.iter().filter(|part| part.kind == Kind::Flag) {
+        let name = part.text.split('=').next().unwrap_or(&part.text);
+        if let Some(excerpt) = documentation.excerpt(name) {
+            if !docs.contains(&excerpt) && docs.len() + excerpt.len() < MAX_DOCS_CHARS {
+                docs.push_str(&excerpt);
+                docs.push('\n');
+            }
+        }
+    }
+    if !docs.is_empty() {
+        prompt.push_str(&format!(
+            "\n\nThese lines from the local documentation describe the options:\n\n```\n{}```",
+            docs
+        ));
+    }
+
+    prompt
+}
+
+// The command with a line dropping down from each part to its explanation, the last part on top
+fn print_breakdown(line: &str, parts: &[Part], response: &str) {
+    let mut explanations = vec![None; parts.len()];
+    for caps in response.lines().filter_map(|l| NUMBERED_LINE.captures(l)) {
+        let index: usize = caps[1].parse().unwrap_or(0);
+        if (1..=parts.len()).contains(&index) {
+            explanations[index - 1] = Some(caps[2].trim().to_string());
+        }
+    }
+
+    // Without a usable list there's nothing to line up, show the answer as it came
+    if explanations.iter().all(Option::is_none) {
+        println!("{}", response);
+        return;
+    }
+
+    println!("  {}", color_command(line, parts));
+    for (i, part) in parts.iter().enumerate().rev() {
+        let mut row = String::new();
+        for earlier in &parts[..i] {
+            pad_to(&mut row, earlier.column);
+            row.push('│');
+        }
+        pad_to(&mut row, part.column);
+
+        let explanation = explanations[i].as_deref().unwrap_or("");
+        println!("  {}└─ {}", row, explanation);
+    }
+
+    if let Some(summary) = response
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("Summary:"))
+    {
+        println!("\n{}", summary.trim());
+    }
+}
+
+fn pad_to(row: &mut String, column: usize) {
+    while row.chars().count() < column {
+        row.push(' ');
+    }
+}
+
+// The command with each part colored by what it is
+fn color_command(line: &str, parts: &[Part]) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut colored = String::new();
+    let mut position = 0;
+
+    for part in parts {
+        // Letters of combined flags share their word, color just the letter
+        let width = match part.kind {
+            Kind::Flag
+                if !part.text.starts_with("--")
+                    && part.column > 0
+                    && chars[part.column - 1] != ' ' =>
+            {
+                1
+            }
+            _ => part.text.chars().count(),
+        };
+        if part.column < position {
+            continue;
+        }
+        colored.extend(&chars[position..part.column]);
+        let text: String = chars[part.column..(part.column + width).min(chars.len())]
+            .iter()
+            .collect();
+        let color = match part.kind {
+            Kind::Program => "green",
+            Kind::Subcommand => "yellow",
+            Kind::Flag => "cyan",
+            Kind::Value | Kind::Argument => "",
+        };
+        colored.push_str(&color_text(&text, color));
+        position = (part.column + width).min(chars.len());
+    }
+    colored.extend(&chars[position..]);
+
+    colored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAR: &str = "NAME
+       tar - an archiving utility
+
+OPTIONS
+       -x, --extract, --get
+              Extract files from an archive.
+       -z, --gzip
+              Filter the archive through gzip.
+       -v, --verbose
+              Verbosely list files processed.
+       -f, --file=ARCHIVE
+              Use archive file or device ARCHIVE.
+       -C, --directory=DIR
+              Change to DIR before performing any operations.
+       --color[=WHEN]
+              Colorize the output.";
+
+    fn parts(line: &str) -> Vec<(String, &'static str)> {
+        let documentation = Documentation {
+            text: TAR.to_string(),
+        };
+        let (_, parts) = split_command(&split_words(line), &documentation);
+        parts
+            .into_iter()
+            .map(|part| {
+                let kind = match part.kind {
+                    Kind::Program => "program",
+                    Kind::Subcommand => "subcommand",
+                    Kind::Flag => "flag",
+                    Kind::Value => "value",
+                    Kind::Argument => "argument",
+                };
+                (part.text, kind)
+            })
+            .collect()
+    }
+
+    fn expected(parts: &[(&str, &'static str)]) -> Vec<(String, &'static str)> {
+        parts
+            .iter()
+            .map(|(text, kind)| (text.to_string(), *kind))
+            .collect()
+    }
+
+    #[test]
+    fn splits_quoted_words() {
+        assert_eq!(
+            split_words(r#"tar -cf "my backup.tar" 'a b'  c"#),
+            vec!["tar", "-cf", "my backup.tar", "a b", "c"]
+        );
+        assert_eq!(split_words("echo ''"), vec!["echo", ""]);
+    }
+
+    #[test]
+    fn splits_combined_short_flags() {
+        assert_eq!(
+            parts("tar -xzvf backup.tgz"),
+            expected(&[
+                ("tar", "program"),
+                ("-x", "flag"),
+                ("-z", "flag"),
+                ("-v", "flag"),
+                ("-f", "flag"),
+                ("backup.tgz", "value"),
+            ])
+        );
+    }
+
+    #[test]
+    fn takes_the_next_word_as_a_value() {
+        assert_eq!(
+            parts("tar -x -C /opt file"),
+            expected(&[
+                ("tar", "program"),
+                ("-x", "flag"),
+                ("-C", "flag"),
+                ("/opt", "value"),
+                ("file", "argument"),
+            ])
+        );
+        assert_eq!(
+            parts("tar --directory /opt --color auto"),
+            expected(&[
+                ("tar", "program"),
+                ("--directory", "flag"),
+                ("/opt", "value"),
+                ("--color", "flag"),
+                ("auto", "argument"),
+            ])
+        );
+    }
+
+    #[test]
+    fn keeps_inline_values_in_the_flag() {
+        assert_eq!(
+            parts("tar --file=backup.tar notes.txt"),
+            expected(&[
+                ("tar", "program"),
+                ("--file=backup.tar", "flag"),
+                ("notes.txt", "argument"),
+            ])
+        );
+    }
+
+    #[test]
+    fn quotes_arguments_with_spaces_again() {
+        let documentation = Documentation {
+            text: TAR.to_string(),
+        };
+        let (line, parts) =
+            split_command(&split_words(r#"tar -f "my backup.tar""#), &documentation);
+        assert_eq!(line, "tar -f 'my backup.tar'");
+        assert_eq!(parts[2].text, "'my backup.tar'");
+        assert!(parts[2].kind == Kind::Value);
+        assert_eq!(parts[2].column, 7);
+    }
+
+    #[test]
+    fn reads_values_from_option_specs() {
+        assert!(option_spec_has_value("-f, --file=ARCHIVE", "-f"));
+        assert!(option_spec_has_value("-f, --file=ARCHIVE", "--file"));
+        assert!(option_spec_has_value("-o <file>", "-o"));
+        assert!(!option_spec_has_value("--file=ARCHIVE", "-f"));
+        assert!(!option_spec_has_value("-v, --verbose", "-v"));
+        assert!(!option_spec_has_value("--color[=WHEN]", "--color"));
+    }
+
+    #[test]
+    fn finds_definition_lines() {
+        assert!(defines("       -f, --file=ARCHIVE", "-f"));
+        assert!(defines("       -f, --file=ARCHIVE", "--file"));
+        assert!(!defines("       --file=ARCHIVE", "-f"));
+        assert!(!defines("              Use archive file -f.", "-f"));
+    }
+}
@@ -0,0 +1,66 @@
+// src/commands/export.rs
+
+// `export <session-id>` writes a saved session as Markdown or standalone HTML.
+
+use super::flag_value;
+use crate::session::{export, store};
+use std::fs;
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut session_id = None;
+    let mut format = None;
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--format" => {
+                format = Some(flag_value(args, i, "--format")?);
+                i += 1;
+            }
+            "--output" | "-o" => {
+                output = Some(flag_value(args, i, "--output")?);
+                i += 1;
+            }
+            id if session_id.is_none() && !id.starts_with("--") => {
+                session_id = Some(id.to_string())
+            }
+            other => return Err(format!("Unknown option '{}'\n{}", other, usage()).into()),
+        }
+        i += 1;
+    }
+
+    let session_id = session_id.ok_or_else(usage)?;
+
+    // Without --format, the output file's extension decides, falling back to Markdown
+    let format = format.unwrap_or_else(|| match output.as_deref() {
+        Some(path) if path.ends_with(".html") || path.ends_with(".htm") => "html".to_string(),
+        _ => "md".to_string(),
+    });
+
+    let session = store::load(&session_id)?;
+    let (rendered, extension) = match format.as_str() {
+        "md" | "markdown" => (export::to_markdown(&session), "md"),
+        "html" => (export::to_html(&session), "html"),
+        other => return Err(format!("Unknown export format '{}', use md or html", other).into()),
+    };
+
+    match output.as_deref() {
+        Some("-") => print!("{}", rendered),
+        Some(path) => {
+            fs::write(path, rendered)?;
+            println!("Exported {} to {}", session.id, path);
+        }
+        None => {
+            let path = format!("{}.{}", session.id, extension);
+            fs::write(&path, rendered)?;
+            println!("Exported {} to {}", session.id, path);
+        }
+    }
+
+    Ok(())
+}
+
+fn usage() -> String {
+    "Usage: export <session-id> [--format md|html] [--output PATH|-]".to_string()
+}
@@ -0,0 +1,129 @@
+// src/commands/fix.rs
+
+// `fix [-cl|-gpt] [--rerun] [--command <cmd>]` corrects the command that was just run in the shell.
+//
+// The command comes from the shell's history file. Its output is taken from the last `chat run` when that
+// ran the same command here, otherwise the command can be run again to capture it. The corrected command
+// comes back in command style, with the same danger warnings, and can be run straight away.
+
+use super::run::{diagnosis_prompt, load_last_run, LastRun};
+use super::{flag_value, pick_model};
+use crate::conversation::Conversation;
+use crate::danger;
+use crate::process::run_teed;
+use crate::prompt::{ask_typed_yes, ask_yes_no};
+use crate::session::Session;
+use crate::shell_history::{last_command, read_history};
+use crate::step_runner::run_response_steps;
+use crate::text_formatter::{color_text, danger_badge};
+use std::env;
+use std::process::Command;
+
+const TAIL_LINES: usize = 80;
+
+// Command style strips prose from the reply, so only the command is asked for
+const QUESTION: &str = "Reply with the corrected command that does what I meant.";
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (model, rest) = pick_model(args);
+
+    let mut rerun = false;
+    let mut command = None;
+    let mut i = 0;
+    while i < rest.len() {
+        match rest[i].as_str() {
+            "--rerun" => rerun = true,
+            "--command" => {
+                command = Some(flag_value(rest, i, "--command")?);
+                i += 1;
+            }
+            other => {
+                return Err(format!(
+                    "Unknown option '{}'\nUsage: fix [-cl|-gpt] [--rerun] [--command <cmd>]",
+                    other
+                )
+                .into())
+            }
+        }
+        i += 1;
+    }
+
+    let command = match command {
+        Some(command) => command,
+        None => {
+            let history = read_history()?;
+            last_command(&history)
+                .ok_or("No earlier command found in the shell history")?
+                .to_string()
+        }
+    };
+    println!("Fixing: {}", color_text(&command, "cyan"));
+
+    let cwd = env::current_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_default();
+    let last_run = match load_last_run().filter(|run| run.command == command && run.cwd == cwd) {
+        Some(run) => run,
+        None if rerun || ask_yes_no("Run it again to see what it prints?")? => {
+            match rerun_command(&command, &cwd)? {
+                Some(run) => run,
+                None => return Ok(()),
+            }
+        }
+        None => uncaptured(&command, &cwd),
+    };
+
+    // Ask in command style so the answer gets the same rendering, warnings and step runner
+    let session = Session::new(model.provider(), model.model_name(), Some("command"));
+    let mut conversation = Conversation::new(model, session);
+    let response = conversation
+        .send(&diagnosis_prompt(&last_run, QUESTION))
+        .await?;
+    conversation.print_response(&response);
+
+    if ask_yes_no("\nRun the corrected command?")? {
+        run_response_steps(&mut conversation, &response).await?;
+    }
+    Ok(())
+}
+
+// Runs the command again and captures its output. Returns None if it worked this time. A dangerous
+// command is only run again after a typed "yes", otherwise the fix is asked for without its output.
+fn rerun_command(command: &str, cwd: &str) -> Result<Option<LastRun>, Box<dyn std::error::Error>> {
+    let explanations = danger::check(command);
+    if !explanations.is_empty() {
+        for explanation in &explanations {
+            println!("{} {}", danger_badge(), explanation);
+        }
+        if !ask_typed_yes("Type yes to run it again anyway:")? {
+            return Ok(Some(uncaptured(command, cwd)));
+        }
+    }
+
+    let shell = env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    let captured = run_teed(Command::new(shell).arg("-c").arg(command), true, TAIL_LINES)?;
+    if captured.status.success() {
+        println!("\nIt worked this time, nothing to fix.");
+        return Ok(None);
+    }
+
+    Ok(Some(LastRun {
+        command: command.to_string(),
+        cwd: cwd.to_string(),
+        status: captured.status.to_string(),
+        success: false,
+        stdout: captured.stdout,
+        stderr: captured.stderr,
+    }))
+}
+
+fn uncaptured(command: &str, cwd: &str) -> LastRun {
+    LastRun {
+        command: command.to_string(),
+        cwd: cwd.to_string(),
+        status: "output not captured".to_string(),
+        success: false,
+        stdout: String::new(),
+        stderr: String::new(),
+    }
+}
@@ -0,0 +1,125 @@
+// src/commands/history.rs
+
+// `history list` shows saved sessions, `history search` runs a ranked full-text search over them.
+
+use super::flag_value;
+use crate::session::search::{SearchFilter, SearchIndex};
+use crate::session::{store, timestamp};
+use crate::text_formatter::color_text;
+
+const DEFAULT_LIMIT: usize = 20;
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    match args.first().map(String::as_str) {
+        Some("list") => list(&args[1..]),
+        Some("search") => search(&args[1..]),
+        _ => Err(usage().into()),
+    }
+}
+
+fn usage() -> String {
+    [
+        "Usage:",
+        "  history list [--limit N]",
+        "  history search \"query\" [--provider gpt|claude] [--mode short|command|none]",
+        "                         [--since YYYY-MM-DD] [--until YYYY-MM-DD] [--limit N]",
+    ]
+    .join("\n")
+}
+
+fn list(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut limit = DEFAULT_LIMIT;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--limit" => {
+                limit = flag_value(args, i, "--limit")?.parse()?;
+                i += 1;
+            }
+            other => return Err(format!("Unknown option '{}'\n{}", other, usage()).into()),
+        }
+        i += 1;
+    }
+
+    let sessions = store::load_all()?;
+    if sessions.is_empty() {
+        println!("No saved sessions yet.");
+        return Ok(());
+    }
+
+    for session in sessions.iter().take(limit) {
+        println!(
+            "{}  {}  {:<6}  {}",
+            color_text(&session.id, "cyan"),
+            timestamp::format_datetime(session.updated_at),
+            session.provider,
+            session.title
+        );
+    }
+    Ok(())
+}
+
+fn search(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut filter = SearchFilter::default();
+    let mut limit = DEFAULT_LIMIT;
+    let mut query_words = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--provider" => {
+                filter.provider = Some(flag_value(args, i, "--provider")?);
+                i += 1;
+            }
+            "--mode" => {
+                filter.mode = Some(flag_value(args, i, "--mode")?);
+                i += 1;
+            }
+            "--since" => {
+                filter.since = Some(timestamp::parse_date(&flag_value(args, i, "--since")?)?);
+                i += 1;
+            }
+            "--until" => {
+                filter.until = Some(timestamp::parse_date_end(&flag_value(args, i, "--until")?)?);
+                i += 1;
+            }
+            "--limit" => {
+                limit = flag_value(args, i, "--limit")?.parse()?;
+                i += 1;
+            }
+            word => query_words.push(word.to_string()),
+        }
+        i += 1;
+    }
+
+    let query = query_words.join(" ");
+    if query.trim().is_empty() {
+        return Err(usage().into());
+    }
+
+    let sessions = store::load_all()?;
+    let index = SearchIndex::build(&sessions);
+    let hits = index.search(&query, &filter, limit);
+
+    if hits.is_empty() {
+        println!("No matches for \"{}\".", query);
+        return Ok(());
+    }
+
+    for (rank, hit) in hits.iter().enumerate() {
+        println!(
+            "{}. {}  {}  {}{}  {}",
+            rank + 1,
+            color_text(&hit.session_id, "cyan"),
+            timestamp::format_datetime(hit.timestamp),
+            hit.provider,
+            hit.mode
+                .as_ref()
+                .map(|mode| format!(" ({})", mode))
+                .unwrap_or_default(),
+            hit.session_title
+        );
+        println!("   {}: {}\n", hit.role.label(), hit.snippet);
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+// src/commands/import.rs
+
+// `import <conversations.json>` converts a ChatGPT or Claude web export into saved sessions.
+// Re-importing the same file updates the sessions it created before instead of duplicating them.
+
+use super::flag_value;
+use crate::session::import::{parse_export, ExportFormat};
+use crate::session::store;
+use std::collections::HashMap;
+use std::fs;
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut path = None;
+    let mut format = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--format" => {
+                let name = flag_value(args, i, "--format")?;
+                format = Some(ExportFormat::from_name(&name).ok_or_else(|| {
+                    format!("Unknown export format '{}', use chatgpt or claude", name)
+                })?);
+                i += 1;
+            }
+            file if path.is_none() && !file.starts_with("--") => path = Some(file.to_string()),
+            other => return Err(format!("Unknown option '{}'\n{}", other, usage()).into()),
+        }
+        i += 1;
+    }
+
+    let path = path.ok_or_else(usage)?;
+    let sessions = parse_export(&fs::read_to_string(&path)?, format)?;
+
+    // Map each previously imported conversation to the session id it was saved under
+    let existing: HashMap<String, String> = store::load_all()?
+        .into_iter()
+        .filter_map(|session| session.source.map(|source| (source, session.id)))
+        .collect();
+
+    let (mut created, mut updated) = (0, 0);
+    for mut session in sessions {
+        match session
+            .source
+            .as_ref()
+            .and_then(|source| existing.get(source))
+        {
+            Some(id) => {
+                session.id = id.clone();
+                updated += 1;
+            }
+            None => created += 1,
+        }
+        store::save(&session)?;
+    }
+
+    println!(
+        "Imported {} new and updated {} existing conversations from {}",
+        created, updated, path
+    );
+    Ok(())
+}
+
+fn usage() -> String {
+    "Usage: import <conversations.json> [--format chatgpt|claude]\n\
+     Unzip the data export from chatgpt.com or claude.ai first, then point this at its conversations.json."
+        .to_string()
+}
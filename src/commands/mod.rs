@@ -0,0 +1,85 @@
+// src/commands/mod.rs
+
+// Subcommands that run instead of a one-off prompt, e.g. `chat history search "rsync flags"`.
+// Each submodule exposes a `run` function that receives the arguments following the subcommand name.
+
+pub mod commit;
+pub mod completions;
+pub mod explain;
+pub mod export;
+pub mod fix;
+pub mod history;
+pub mod import;
+pub mod resume;
+pub mod review;
+pub mod run;
+pub mod shell_init;
+pub mod suggest;
+
+use crate::config::config;
+use crate::models::{claude::Claude, from_provider, gpt::GPT, AIModel};
+
+// Names accepted by dispatch, also offered by the shell completion scripts
+pub const SUBCOMMANDS: &[&str] = &[
+    "commit",
+    "completions",
+    "explain",
+    "export",
+    "fix",
+    "history",
+    "import",
+    "resume",
+    "review",
+    "run",
+    "shell-init",
+    "suggest",
+];
+
+// Pulls the value following a `--flag` out of the argument list
+pub fn flag_value(args: &[String], index: usize, flag: &str) -> Result<String, String> {
+    args.get(index + 1)
+        .cloned()
+        .ok_or_else(|| format!("{} requires a value", flag))
+}
+
+// The model picked by a leading -cl or -gpt and the arguments after it and an optional `--`. Without either
+// flag it's the provider set in the config file, or GPT.
+pub fn pick_model(args: &[String]) -> (Box<dyn AIModel>, &[String]) {
+    let mut model: Box<dyn AIModel> = config()
+        .provider
+        .as_deref()
+        .and_then(from_provider)
+        .unwrap_or_else(|| Box::new(GPT::new()));
+    let mut rest = args;
+    while let Some((first, remaining)) = rest.split_first() {
+        match first.as_str() {
+            "-cl" => model = Box::new(Claude::new()),
+            "-gpt" => model = Box::new(GPT::new()),
+            "--" => return (model, remaining),
+            _ => break,
+        }
+        rest = remaining;
+    }
+    (model, rest)
+}
+
+// Runs the subcommand named by the first argument, or returns None if it isn't one
+pub async fn dispatch(args: &[String]) -> Option<Result<(), Box<dyn std::error::Error>>> {
+    let rest = args.get(1..).unwrap_or(&[]);
+
+    match args.first().map(String::as_str) {
+        Some("commit") => Some(commit::run(rest).await),
+        Some("completions") => Some(completions::run(rest)),
+        Some("explain") => Some(explain::run(rest).await),
+        Some("export") => Some(export::run(rest)),
+        Some("fix") => Some(fix::run(rest).await),
+        Some("history") => Some(history::run(rest)),
+        Some("import") => Some(import::run(rest)),
+        Some("resume") => Some(resume::run(rest).await),
+        Some("review") => Some(review::run(rest).await),
+        Some("run") => Some(run::run(rest).await),
+        Some("shell-init") => Some(shell_init::run(rest)),
+        Some("suggest") => Some(suggest::run(rest).await),
+        _ => None,
+    }
+}
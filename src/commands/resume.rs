@@ -0,0 +1,41 @@
+// src/commands/resume.rs
+
+// `resume <session-id>` picks a saved or imported session back up where its active branch left off.
+
+use crate::conversation::{continue_conversation, Conversation};
+use crate::models::from_provider;
+use crate::response_types::post_process_for;
+use crate::session::{store, Role};
+use crate::text_formatter::color_text;
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let session_id = args.first().ok_or("Usage: resume <session-id>")?;
+    let session = store::load(session_id)?;
+
+    let model = from_provider(&session.provider).ok_or_else(|| {
+        format!(
+            "Unknown provider '{}' in session {}",
+            session.provider, session.id
+        )
+    })?;
+    let mut conversation = Conversation::new(model, session);
+    conversation.load_model()?;
+
+    // Show where the conversation left off before handing over to the prompt
+    let path = conversation.session.path();
+    println!(
+        "Resuming {} ({} turns)",
+        color_text(&conversation.session.id, "cyan"),
+        path.len()
+    );
+    if let Some(last_prompt) = path.iter().rev().find(|turn| turn.role == Role::User) {
+        println!("\n{}{}", color_text("You: ", "yellow"), last_prompt.content);
+    }
+    if let Some(last) = path.last().filter(|turn| turn.role == Role::Assistant) {
+        let response = post_process_for(last.mode.as_deref(), &last.content);
+        conversation.print_response(&response);
+    }
+
+    println!("\nAt anytime, enter quit to exit the conversation, or /help to see commands.");
+    continue_conversation(conversation).await
+}
@@ -0,0 +1,197 @@
+// src/commands/review.rs
+
+// `review [-cl|-gpt] [<range>|--staged]` asks the model to review a diff. Without a range it reviews the
+// uncommitted changes (`git diff HEAD`), otherwise anything `git diff` accepts, e.g. `main...HEAD` or `HEAD~3`.
+//
+// The hunks are numbered per file in the prompt and the model answers with a JSON list of findings, each
+// pointing at a file and hunk. Findings are printed under the highlighted hunk they're about. From there
+// the conversation can be continued, e.g. to ask for a fix.
+
+use super::pick_model;
+use crate::conversation::{continue_conversation, Conversation};
+use crate::file_edits::language_for_path;
+use crate::git::{self, split_diff, DiffFile};
+use crate::prompt::ask_yes_no;
+use crate::session::Session;
+use crate::syntax_highlighter::highlight_diff;
+use crate::text_formatter::{color_text, create_header, print_formatted_response};
+use serde::Deserialize;
+
+const SYSTEM_PROMPT: &str = "You are an experienced code reviewer. Point out bugs, risky changes, missing error handling and unclear code. \
+                             Don't comment on what's fine, and don't restate what the change does. \
+                             Reply with only a JSON array of findings, each an object with the fields \
+                             \"file\" (the path as given), \"hunk\" (the hunk number, or null for the whole file), \
+                             \"severity\" (one of \"bug\", \"risk\", \"suggestion\" or \"nit\") and \"comment\". \
+                             Reply with [] when there is nothing to point out.";
+
+// Files past this much diff are left out of the prompt
+const MAX_DIFF_CHARS: usize = 40000;
+
+#[derive(Deserialize)]
+struct Finding {
+    file: String,
+    #[serde(default)]
+    hunk: Option<usize>,
+    #[serde(default)]
+    severity: String,
+    comment: String,
+}
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (model, rest) = pick_model(args);
+    let usage = "Usage: review [-cl|-gpt] [<range>|--staged]";
+
+    let (range, label) = match rest {
+        [] => ("HEAD", "uncommitted changes".to_string()),
+        [staged] if staged == "--staged" => ("--staged", "staged changes".to_string()),
+        [range] if !range.starts_with('-') => (range.as_str(), range.clone()),
+        _ => return Err(usage.into()),
+    };
+
+    // The -- keeps a range from being read as a path
+    let diff = git::diff(&[range, "--"])?;
+    let files = split_diff(&diff);
+    if files.iter().all(|file| file.hunks.is_empty()) {
+        return Err(format!("No changes to review in {}", label).into());
+    }
+
+    let (prompt, skipped) = build_prompt(&files, &label);
+    if !skipped.is_empty() {
+        println!(
+            "{}",
+            color_text(
+                &format!("The diff is too large, leaving out {}", skipped.join(", ")),
+                "yellow"
+            )
+        );
+    }
+
+    let mut session = Session::new(model.provider(), model.model_name(), None);
+    session.system = Some(SYSTEM_PROMPT.to_string());
+    let mut conversation = Conversation::new(model, session);
+
+    println!("Reviewing {}...", label);
+    let response = conversation.send(&prompt).await?;
+
+    match parse_findings(&response) {
+        Some(findings) => print_findings(&files, &findings),
+        // Not JSON after all, the text is still a review
+        None => print_formatted_response(&response, false),
+    }
+
+    if ask_yes_no("\nWould you like to continue the conversation?")? {
+        println!("\nAt anytime, enter quit to exit the conversation, or /help to see commands.");
+        continue_conversation(conversation).await?;
+    }
+    Ok(())
+}
+
+// The prompt and the files that didn't fit in it
+fn build_prompt(files: &[DiffFile], label: &str) -> (String, Vec<String>) {
+    let mut prompt = format!(
+        "Review these {}. The hunks of each file are numbered.\n",
+        label
+    );
+    let mut skipped = Vec::new();
+
+    for file in files.iter().filter(|file| !file.hunks.is_empty()) {
+        let mut text = format!("\nFile {}\n", file.path);
+        for (number, hunk) in file.hunks.iter().enumerate() {
+            text.push_str(&format!("Hunk {}:\n{}", number + 1, hunk));
+        }
+
+        if prompt.len() + text.len() > MAX_DIFF_CHARS {
+            skipped.push(file.path.clone());
+        } else {
+            prompt.push_str(&text);
+        }
+    }
+    (prompt, skipped)
+}
+
+// The JSON array in the response, which may be wrapped in a code fence or a sentence
+fn parse_findings(response: &str) -> Option<Vec<Finding>> {
+    let start = response.find('[')?;
+    let end = response.rfind(']')?;
+    serde_json::from_str(response.get(start..=end)?).ok()
+}
+
+fn print_findings(files: &[DiffFile], findings: &[Finding]) {
+    println!("\n{}", create_header());
+    if findings.is_empty() {
+        println!("{}", color_text("No findings.", "green"));
+        return;
+    }
+
+    for file in files {
+        let for_file: Vec<&Finding> = findings.iter().filter(|f| f.file == file.path).collect();
+        if for_file.is_empty() {
+            continue;
+        }
+        println!("\n{}", color_text(&file.path, "cyan"));
+
+        // Findings about the whole file, or a hunk that doesn't exist, come first
+        for finding in &for_file {
+            if !finding
+                .hunk
+                .is_some_and(|n| n >= 1 && n <= file.hunks.len())
+            {
+                print_finding(finding);
+            }
+        }
+
+        let language = language_for_path(&file.path);
+        for (number, hunk) in file.hunks.iter().enumerate() {
+            let for_hunk: Vec<&&Finding> = for_file
+                .iter()
+                .filter(|f| f.hunk == Some(number + 1))
+                .collect();
+            if for_hunk.is_empty() {
+                continue;
+            }
+
+            print!("\n{}", highlight_diff(language, hunk));
+            for finding in for_hunk {
+                print_finding(finding);
+            }
+        }
+    }
+
+    // Files the model named that aren't in the diff, usually a mistyped path
+    let other: Vec<&Finding> = findings
+        .iter()
+        .filter(|f| !files.iter().any(|file| file.path == f.file))
+        .collect();
+    if !other.is_empty() {
+        println!("\n{}", color_text("Other", "cyan"));
+        for finding in other {
+            print!("  {}: ", finding.file);
+            print_finding(finding);
+        }
+    }
+
+    let file_count = files
+        .iter()
+        .filter(|file| findings.iter().any(|f| f.file == file.path))
+        .count();
+    println!("\n{} findings in {} files.", findings.len(), file_count);
+}
+
+fn print_finding(finding: &Finding) {
+    let color = match finding.severity.as_str() {
+        "bug" => "red",
+        "risk" => "yellow",
+        "suggestion" => "cyan",
+        _ => "",
+    };
+    let severity = if finding.severity.is_empty() {
+        "note"
+    } else {
+        &finding.severity
+    };
+    println!(
+        "  {} {}",
+        color_text(&format!("[{}]", severity), color),
+        finding.comment
+    );
+}
@@ -0,0 +1,151 @@
+// src/commands/run.rs
+
+// `run [-cl|-gpt] -- <command>` runs a command with its output passed through as usual. If it fails, the
+// end of its output goes to the model along with the command and working directory, and the model's
+// diagnosis is printed. From there the conversation can be continued like any other.
+//
+// The tool exits with the command's exit code, so it can stand in for the command in scripts.
+
+use super::pick_model;
+use crate::conversation::{continue_conversation, Conversation};
+use crate::process::run_teed;
+use crate::prompt::ask_yes_no;
+use crate::session::store::data_dir;
+use crate::session::Session;
+use crate::text_formatter::format_error;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+// How much of each stream is kept for the model
+const TAIL_LINES: usize = 80;
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (model, words) = pick_model(args);
+    if words.is_empty() {
+        return Err("Usage: run [-cl|-gpt] -- <command> [args...]".into());
+    }
+
+    // A single argument like "cargo test | tail" is a shell command line, several are run as they are
+    let mut command = match words {
+        [line] => {
+            let shell = env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+            let mut command = Command::new(shell);
+            command.arg("-c").arg(line);
+            command
+        }
+        [program, args @ ..] => {
+            let mut command = Command::new(program);
+            command.args(args);
+            command
+        }
+        [] => unreachable!(),
+    };
+    let shown = words.join(" ");
+
+    let captured = run_teed(&mut command, true, TAIL_LINES)
+        .map_err(|e| format!("Could not run {}: {}", shown, e))?;
+
+    let cwd = env::current_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_default();
+    let last_run = LastRun {
+        command: shown.clone(),
+        cwd,
+        status: captured.status.to_string(),
+        success: captured.status.success(),
+        stdout: captured.stdout,
+        stderr: captured.stderr,
+    };
+    save_last_run(&last_run);
+    if last_run.success {
+        return Ok(());
+    }
+
+    let code = captured.status.code().unwrap_or(1);
+    eprintln!(
+        "\n{}",
+        format_error(&format!("{} failed ({})", shown, captured.status))
+    );
+
+    let prompt = diagnosis_prompt(
+        &last_run,
+        "What is the cause of the failure and how do I fix it?",
+    );
+
+    let session = Session::new(model.provider(), model.model_name(), None);
+    let mut conversation = Conversation::new(model, session);
+    println!(
+        "Asking {} what went wrong...",
+        conversation.model.model_name()
+    );
+
+    match conversation.send(&prompt).await {
+        Ok(response) => {
+            conversation.print_response(&response);
+
+            if ask_yes_no("\nWould you like to continue the conversation?")? {
+                println!(
+                    "\nAt anytime, enter quit to exit the conversation, or /help to see commands."
+                );
+                continue_conversation(conversation).await?;
+            }
+        }
+        Err(e) => eprintln!("{}", format_error(&e.to_string())),
+    }
+
+    std::process::exit(code);
+}
+
+// What the last `run` captured, kept so `fix` can use the output without running the command again
+#[derive(Serialize, Deserialize)]
+pub struct LastRun {
+    pub command: String,
+    pub cwd: String,
+    pub status: String,
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+fn last_run_path() -> PathBuf {
+    data_dir().join("last_run.json")
+}
+
+// Best effort, `run` works the same without it
+fn save_last_run(last_run: &LastRun) {
+    let path = last_run_path();
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string(last_run) {
+        let _ = fs::write(path, json);
+    }
+}
+
+pub fn load_last_run() -> Option<LastRun> {
+    let text = fs::read_to_string(last_run_path()).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+// The command, where it ran and the end of its output, followed by the question
+pub fn diagnosis_prompt(last_run: &LastRun, question: &str) -> String {
+    let mut prompt = format!(
+        "I ran `{}` in `{}` and it failed ({}).",
+        last_run.command, last_run.cwd, last_run.status
+    );
+    for (name, output) in [("stdout", &last_run.stdout), ("stderr", &last_run.stderr)] {
+        if !output.trim().is_empty() {
+            prompt.push_str(&format!(
+                "\n\nThe end of its {}:\n\n```\n{}\n```",
+                name,
+                output.trim_end()
+            ));
+        }
+    }
+    prompt.push_str("\n\n");
+    prompt.push_str(question);
+    prompt
+}
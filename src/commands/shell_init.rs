@@ -0,0 +1,79 @@
+// src/commands/shell_init.rs
+
+// `shell-init <bash|zsh|fish> [-cl|-gpt]` prints a snippet that binds Ctrl-G in the shell. Pressing it sends
+// what's typed on the command line to `suggest` and replaces it with the command that comes back, for review
+// before pressing Enter. Meant to be loaded from the shell's startup file:
+//
+//   eval "$(chat shell-init bash)"          # ~/.bashrc
+//   eval "$(chat shell-init zsh)"           # ~/.zshrc
+//   chat shell-init fish | source           # ~/.config/fish/config.fish
+
+use std::env;
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let usage = "Usage: shell-init <bash|zsh|fish> [-cl|-gpt]";
+    let shell = args.first().ok_or(usage)?;
+    let model_flag = match args.get(1).map(String::as_str) {
+        Some(flag @ ("-cl" | "-gpt")) => format!(" {}", flag),
+        None => String::new(),
+        Some(other) => return Err(format!("Unknown option '{}'\n{}", other, usage).into()),
+    };
+
+    // The full path of this binary, so the widget works without an alias or PATH entry
+    let program = env::current_exe()?.display().to_string();
+    let program = shell_quote(&program);
+
+    let snippet = match shell.as_str() {
+        "bash" => format!(
+            r#"_console_ai_chat_widget() {{
+    local command
+    command=$({program} suggest{model_flag} -- "$READLINE_LINE" 2>/dev/tty) || return
+    READLINE_LINE=$command
+    READLINE_POINT=${{#READLINE_LINE}}
+}}
+bind -x '"\C-g": _console_ai_chat_widget'
+"#
+        ),
+        "zsh" => format!(
+            r#"_console_ai_chat_widget() {{
+    local command
+    command=$({program} suggest{model_flag} -- "$BUFFER" 2>/dev/tty)
+    if [[ $? -eq 0 ]]; then
+        BUFFER=$command
+        CURSOR=${{#BUFFER}}
+    fi
+    zle reset-prompt
+}}
+zle -N _console_ai_chat_widget
+bindkey '^G' _console_ai_chat_widget
+"#
+        ),
+        "fish" => format!(
+            r#"function _console_ai_chat_widget
+    set -l command ({program} suggest{model_flag} -- (commandline) 2>/dev/tty)
+    and commandline --replace -- $command
+    commandline --function repaint
+end
+bind \cg _console_ai_chat_widget
+"#
+        ),
+        other => {
+            return Err(format!("Unsupported shell '{}', use bash, zsh or fish", other).into())
+        }
+    };
+
+    print!("{}", snippet);
+    Ok(())
+}
+
+// Single quotes keep paths with spaces intact in all three shells
+pub fn shell_quote(text: &str) -> String {
+    if text
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "/._-+".contains(c))
+    {
+        text.to_string()
+    } else {
+        format!("'{}'", text.replace('\'', r"'\''"))
+    }
+}
@@ -0,0 +1,147 @@
+// src/commands/suggest.rs
+
+// `suggest [-cl|-gpt] -- <what you want to do>` turns a description into a single command line.
+// It's what the shell widget from `shell-init` calls, so its output is a contract other programs rely on:
+//
+//   - On success it exits with 0 and prints exactly one line to stdout: the command. No prose, no color.
+//   - On failure it exits with 1, prints the error to stderr and nothing to stdout. That includes a reply
+//     that only works as a multi-line script.
+//   - Danger warnings for the command go to stderr, they never change stdout.
+
+use super::pick_model;
+use crate::conversation::Conversation;
+use crate::danger;
+use crate::environment;
+use crate::session::Session;
+use crate::steps::extract_steps;
+
+const SYSTEM_PROMPT: &str = "You turn a description of a task into a single shell command line. \
+                             Reply with only the command, on one line, with no explanation, no markdown and no code fences. \
+                             Join several commands with && if they are needed.";
+
+pub async fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (model, words) = pick_model(args);
+    let request = words.join(" ");
+    if request.trim().is_empty() {
+        return Err("Usage: suggest [-cl|-gpt] -- <what you want to do>".into());
+    }
+
+    let mut session = Session::new(model.provider(), model.model_name(), None);
+    session.system = Some(match environment::context() {
+        Some(context) => format!("{}\n\n{}", SYSTEM_PROMPT, context),
+        None => SYSTEM_PROMPT.to_string(),
+    });
+    let mut conversation = Conversation::new(model, session);
+    let response = conversation.send(&request).await?;
+
+    let command = single_command(&response)
+        .ok_or("The model did not reply with a command that fits on one line")?;
+    for explanation in danger::check(&command) {
+        eprintln!("DANGER: {}", explanation);
+    }
+
+    println!("{}", command);
+    Ok(())
+}
+
+// Models don't always follow the instructions: take the steps if they answered with a list or code
+// block, otherwise the first line. Several lines are joined with && only when each is a whole command,
+// a script with loops, continued lines or here-documents can't be put on one line that way.
+fn single_command(response: &str) -> Option<String> {
+    let steps = extract_steps(response);
+    let commands: Vec<String> = if steps.is_empty() {
+        let line = response
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())?;
+        vec![line.trim_matches('`').trim_start_matches("$ ").to_string()]
+    } else {
+        steps.into_iter().map(|step| step.command).collect()
+    };
+
+    let lines: Vec<&str> = commands
+        .iter()
+        .flat_map(|command| command.lines())
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+
+    match lines.as_slice() {
+        [] => None,
+        [line] => Some(line.to_string()),
+        _ if lines.iter().all(|line| is_whole_command(line)) => Some(lines.join(" && ")),
+        _ => None,
+    }
+}
+
+// Whether a line of a script runs by itself: it doesn't go on to the next line, open a block that a later
+// line closes, or sit inside one. Quotes are only counted, an odd number means the string goes on.
+fn is_whole_command(line: &str) -> bool {
+    let words: Vec<&str> = line
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|word| !word.is_empty())
+        .collect();
+    let count = |keywords: &[&str]| words.iter().filter(|word| keywords.contains(word)).count();
+
+    let inside_block = words
+        .first()
+        .is_some_and(|word| ["then", "do", "else", "elif"].contains(word));
+    let balanced =
+        count(&["if", "for", "while", "until", "case", "{"]) == count(&["fi", "done", "esac", "}"]);
+    let continued = ["\\", "|", "&&", "||", "(", "{"]
+        .iter()
+        .any(|end| line.ends_with(end));
+
+    !inside_block
+        && balanced
+        && !continued
+        && !line.contains("<<")
+        && line.matches('"').count().is_multiple_of(2)
+        && line.matches('\'').count().is_multiple_of(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_the_first_line_of_plain_text() {
+        assert_eq!(
+            single_command("`ls -la`\nThis lists the files.").as_deref(),
+            Some("ls -la")
+        );
+    }
+
+    #[test]
+    fn joins_separate_commands() {
+        let response = "```bash\nmkdir build\n# then go there\ncd build\n```";
+        assert_eq!(
+            single_command(response).as_deref(),
+            Some("mkdir build && cd build")
+        );
+    }
+
+    #[test]
+    fn keeps_a_one_line_block() {
+        let response = "```bash\nfor f in *.txt; do wc -l \"$f\"; done\n```";
+        assert_eq!(
+            single_command(response).as_deref(),
+            Some("for f in *.txt; do wc -l \"$f\"; done")
+        );
+    }
+
+    #[test]
+    fn refuses_scripts_that_need_their_newlines() {
+        for script in [
+            "for f in *.txt\ndo\n  wc -l \"$f\"\ndone",
+            "if [ -d build ]; then\n  rm -r build\nfi",
+            "docker run \\\n  --rm alpine",
+            "cat > notes.txt <<EOF\nhello\nEOF",
+            "find . -name '*.log' |\n  xargs rm",
+            "echo 'two\nlines'",
+        ] {
+            let response = format!("```bash\n{}\n```", script);
+            assert_eq!(single_command(&response), None, "{}", script);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+// src/config.rs
+
+// Optional user settings, read once from $CONSOLE_AI_CHAT_HOME/config.json (~/.console_ai_chat/config.json).
+// A missing file means defaults, a broken file is reported and then ignored so the tool still runs.
+
+use crate::session::store::data_dir;
+use crate::text_formatter::format_error;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    // "gpt" or "claude", used by the subcommands when neither -gpt nor -cl is given
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub modifiers: Vec<ModifierConfig>,
+    // Extra patterns for commands to warn about, on top of the built-in ones
+    #[serde(default)]
+    pub danger_rules: Vec<DangerRuleConfig>,
+    #[serde(default)]
+    pub environment: EnvironmentConfig,
+    #[serde(default)]
+    pub code_runner: CodeRunnerConfig,
+}
+
+// What command mode tells the model about this machine. Set values replace the detected ones.
+#[derive(Debug, Deserialize)]
+pub struct EnvironmentConfig {
+    #[serde(default = "default_true")]
+    pub detect: bool,
+    #[serde(default)]
+    pub shell: Option<String>,
+    #[serde(default)]
+    pub os: Option<String>,
+    #[serde(default)]
+    pub package_managers: Option<Vec<String>>,
+}
+
+impl Default for EnvironmentConfig {
+    fn default() -> Self {
+        EnvironmentConfig {
+            detect: true,
+            shell: None,
+            os: None,
+            package_managers: None,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// Limits for code blocks run with /run. The memory and file size limits need a shell with `ulimit`.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct CodeRunnerConfig {
+    pub timeout_secs: u64,
+    pub memory_mb: u64,
+    pub file_size_mb: u64,
+}
+
+impl Default for CodeRunnerConfig {
+    fn default() -> Self {
+        CodeRunnerConfig {
+            timeout_secs: 30,
+            memory_mb: 1024,
+            file_size_mb: 100,
+        }
+    }
+}
+
+// A regular expression matched against each command, e.g.
+// { "pattern": "\\bterraform\\s+destroy\\b", "explanation": "Destroys all managed infrastructure" }
+#[derive(Debug, Deserialize)]
+pub struct DangerRuleConfig {
+    pub pattern: String,
+    #[serde(default)]
+    pub explanation: String,
+}
+
+// A response modifier declared by the user, e.g.
+// { "name": "mac", "flag": "-m", "description": "Answer for macOS", "template": "{prompt} Assume macOS with Homebrew." }
+#[derive(Debug, Deserialize)]
+pub struct ModifierConfig {
+    pub name: String,
+    pub flag: String,
+    #[serde(default)]
+    pub description: String,
+    pub template: String,
+    #[serde(default)]
+    pub system: Option<String>,
+    #[serde(default)]
+    pub renderer: Option<String>,
+    // Names of modifiers this one replaces when both are given
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    // Drop the text around the numbered steps of the reply, like the command style does
+    #[serde(default)]
+    pub strip_prose: bool,
+}
+
+lazy_static! {
+    static ref CONFIG: Config = load();
+}
+
+pub fn config() -> &'static Config {
+    &CONFIG
+}
+
+pub fn config_path() -> PathBuf {
+    data_dir().join("config.json")
+}
+
+fn load() -> Config {
+    let path = config_path();
+    let Ok(text) = fs::read_to_string(&path) else {
+        return Config::default();
+    };
+
+    match serde_json::from_str(&text) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format_error(&format!("Ignoring {}: {}", path.display(), e))
+            );
+            Config::default()
+        }
+    }
+}
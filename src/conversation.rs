@@ -1,33 +1,291 @@
 // src/conversation.rs
 
-use crate::models::AIModel;
+use crate::danger;
+use crate::file_edits::attachment_prompt;
+use crate::line_editor::LineEditor;
+use crate::models::{from_provider, AIModel, GenerationParams, Message, Transcript};
+use crate::response_types::{
+    post_process_for, renderer_for, split_mode_prefix, ModifierStack, Renderer,
+};
+use crate::session::{store, Role, Session};
+use crate::slash_commands::{self, Outcome};
 use crate::text_formatter::{color_text, format_error, print_formatted_response};
-use std::io::{self, Write};
+use std::fs;
+
+// The model being talked to and the session recording the exchange.
+// The session's active branch is the transcript: it is sent in full with every prompt, so the model
+// itself holds no history and can be swapped for another provider on any turn.
+pub struct Conversation {
+    pub model: Box<dyn AIModel>,
+    pub session: Session,
+    // Files from /attach waiting to be sent with the next prompt
+    pub pending_attachments: Vec<String>,
+}
+
+impl Conversation {
+    pub fn new(model: Box<dyn AIModel>, session: Session) -> Self {
+        Conversation {
+            model,
+            session,
+            pending_attachments: Vec::new(),
+        }
+    }
+
+    // Rendering follows the mode the latest reply was written in, not the session's current mode
+    pub fn is_command_mode(&self) -> bool {
+        let mode = self
+            .session
+            .head
+            .and_then(|head| self.session.turns[head].mode.as_deref());
+        renderer_for(mode) == Renderer::Command
+    }
+
+    // Prints a reply, followed in command mode by warnings for its dangerous steps
+    pub fn print_response(&self, response: &str) {
+        let is_command_mode = self.is_command_mode();
+        print_formatted_response(response, is_command_mode);
+        if is_command_mode {
+            danger::print_step_warnings(response);
+        }
+    }
+
+    // Sends a prompt in the session's current mode
+    pub async fn send(&mut self, prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let mode = self.session.mode.clone();
+        self.send_with_mode(prompt, mode.as_deref()).await
+    }
+
+    // Sends a prompt with the given mode's modifier applied, then records and saves both turns
+    pub async fn send_with_mode(
+        &mut self,
+        prompt: &str,
+        mode: Option<&str>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        // Attached files are read now, so the model sees them as they are when the prompt is sent
+        let with_attachments;
+        let prompt = if self.pending_attachments.is_empty() {
+            prompt
+        } else {
+            with_attachments = match attachment_prompt(&self.pending_attachments, prompt) {
+                Ok(text) => text,
+                Err(e) => {
+                    // Dropped, or every later prompt would fail on the same file
+                    self.pending_attachments
+                        .retain(|path| fs::read_to_string(path).is_ok());
+                    return Err(format!(
+                        "{}. It was dropped from the attachments, send the prompt again to go on without it.",
+                        e
+                    )
+                    .into());
+                }
+            };
+            &with_attachments
+        };
+        let response = self.generate(prompt, mode).await?;
+
+        for path in self.pending_attachments.drain(..) {
+            if !self.session.attachments.contains(&path) {
+                self.session.attachments.push(path);
+            }
+        }
+        self.session.push_user(prompt, mode);
+        self.session.push_assistant(
+            &response,
+            self.model.provider(),
+            self.model.model_name(),
+            mode,
+        );
+        save_session(&self.session);
+
+        Ok(post_process_for(mode, &response))
+    }
+
+    // Asks for a new reply to the prompt at `user_index`, dropping anything after it from the active branch.
+    // The old reply and whatever followed it stay in the session as a sibling branch.
+    pub async fn regenerate(
+        &mut self,
+        user_index: usize,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let previous_head = self.session.head;
+        let prompt = self.session.turns[user_index].content.clone();
+        let mode = self.session.turns[user_index].mode.clone();
+
+        self.session.head = self.session.turns[user_index].parent;
+
+        match self.generate(&prompt, mode.as_deref()).await {
+            Ok(response) => {
+                self.session.head = Some(user_index);
+                self.session.push_assistant(
+                    &response,
+                    self.model.provider(),
+                    self.model.model_name(),
+                    mode.as_deref(),
+                );
+                save_session(&self.session);
+                Ok(post_process_for(mode.as_deref(), &response))
+            }
+            Err(e) => {
+                self.session.head = previous_head;
+                Err(e)
+            }
+        }
+    }
+
+    // Sends a replacement for the prompt at `user_index` as a new branch next to the original, in the original's mode
+    pub async fn edit_prompt(
+        &mut self,
+        user_index: usize,
+        prompt: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let previous_head = self.session.head;
+        let mode = self.session.turns[user_index].mode.clone();
+        self.session.head = self.session.turns[user_index].parent;
+
+        let result = self.send_with_mode(prompt, mode.as_deref()).await;
+        if result.is_err() {
+            self.session.head = previous_head;
+        }
+        result
+    }
+
+    // The latest reply on the active branch, as it was shown
+    pub fn last_response(&self) -> Option<String> {
+        self.session
+            .path()
+            .iter()
+            .rev()
+            .find(|turn| turn.role == Role::Assistant)
+            .map(|turn| post_process_for(turn.mode.as_deref(), &turn.content))
+    }
+
+    // Creates the model for the session's current provider and model name, e.g. after /provider or /load
+    pub fn load_model(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut model = from_provider(&self.session.provider)
+            .ok_or_else(|| format!("Unknown provider '{}'", self.session.provider))?;
+
+        model.set_model(&self.session.model);
+        self.model = model;
+        Ok(())
+    }
+
+    // The active branch followed by the new prompt. A modifier's system instructions are added to the
+    // session's own system prompt.
+    fn transcript(
+        &self,
+        prompt: &str,
+        modifier_system: Option<&str>,
+        params: GenerationParams,
+    ) -> Transcript {
+        let mut messages: Vec<Message> = self
+            .session
+            .path()
+            .into_iter()
+            .map(|turn| Message {
+                role: turn.role,
+                content: turn.content.clone(),
+            })
+            .collect();
+
+        messages.push(Message {
+            role: Role::User,
+            content: prompt.to_string(),
+        });
+
+        let system = match (self.session.system.as_deref(), modifier_system) {
+            (Some(session), Some(modifier)) => Some(format!("{}\n\n{}", session, modifier)),
+            (session, modifier) => session.or(modifier).map(str::to_string),
+        };
+
+        Transcript {
+            system,
+            messages,
+            params,
+        }
+    }
+
+    async fn generate(
+        &mut self,
+        prompt: &str,
+        mode: Option<&str>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        // The modifiers shape the request. The reply is returned as it came, to be saved that way.
+        let stack = mode.map_or_else(ModifierStack::default, ModifierStack::from_mode);
+        let transcript = self.transcript(
+            &stack.apply(prompt),
+            stack.system_prompt().as_deref(),
+            stack.params(),
+        );
+
+        self.model.generate_response(&transcript).await
+    }
+}
 
 pub async fn continue_conversation(
-    mut model: Box<dyn AIModel>,
-    is_command_mode: bool,
+    mut conversation: Conversation,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    // Start conversation loop, break on "quit"
+    let mut line_editor = LineEditor::new()?;
+    let you = color_text("You: ", "yellow");
+
+    // Start conversation loop, break on "quit" or /quit
     loop {
-        let you = color_text("You: ", "yellow");
-        print!("\n{}", you);
+        // Blank line between the previous response and the next prompt
+        println!();
 
-        // Flush stdout to ensure prompt is displayed, ensuring printing breaks between prompts and responses
-        io::stdout().flush()?;
+        let Some(input) = line_editor.read_prompt(&you)? else {
+            // End of input (Ctrl-D)
+            println!("Conversation ended.");
+            break;
+        };
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+        let input = input.trim();
+        if input.is_empty() {
+            continue;
+        }
 
-        if input.trim().to_lowercase() == "quit" {
+        if input.to_lowercase() == "quit" {
             println!("Conversation ended.");
             break;
         }
 
-        // Same as in main.rs, modify the prompt if a response modifier is provided
-        match model.generate_response(&input).await {
+        // Lines like "/mode short" are handled locally instead of being sent to the model
+        let composed;
+        let prompt = if slash_commands::is_slash_command(input) {
+            match slash_commands::handle(&mut conversation, input).await {
+                Outcome::Continue => continue,
+                Outcome::Send(text) => {
+                    composed = text;
+                    composed.as_str()
+                }
+                Outcome::Quit => {
+                    println!("Conversation ended.");
+                    break;
+                }
+            }
+        } else {
+            // A leading "//" sends a prompt that really starts with a slash
+            input
+                .strip_prefix('/')
+                .filter(|rest| rest.starts_with('/'))
+                .unwrap_or(input)
+        };
+
+        // Flags like "-s", "-s -c" or "-n" in front of a prompt pick the mode for this turn only,
+        // a leading backslash as in "\-r flag of cp" sends them to the model as written
+        let result = if let Some(literal) = prompt
+            .strip_prefix('\\')
+            .filter(|rest| rest.starts_with('-'))
+        {
+            conversation.send(literal).await
+        } else {
+            match split_mode_prefix(prompt) {
+                Some((mode, rest)) => conversation.send_with_mode(rest, mode.as_deref()).await,
+                None => conversation.send(prompt).await,
+            }
+        };
+
+        match result {
             Ok(response) => {
-                print_formatted_response(&response, is_command_mode);
+                conversation.print_response(&response);
             }
 
             Err(e) => eprintln!("{}", format_error(&e.to_string())),
@@ -35,3 +293,13 @@ pub async fn continue_conversation(
     }
     Ok(())
 }
+
+// Saving is best effort, a failed write shouldn't interrupt the conversation
+pub fn save_session(session: &Session) {
+    if let Err(e) = store::save(session) {
+        eprintln!(
+            "{}",
+            format_error(&format!("Could not save session: {}", e))
+        );
+    }
+}
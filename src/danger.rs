@@ -0,0 +1,160 @@
+// src/danger.rs
+
+// Checks commands from command mode answers against patterns known to destroy data or open up a machine,
+// so they stand out before anyone copies or runs them. Extra rules can be added in the config file:
+// { "danger_rules": [ { "pattern": "\\bkubectl\\s+delete\\b", "explanation": "Deletes cluster resources" } ] }
+
+use crate::config::config;
+use crate::steps::extract_steps;
+use crate::text_formatter::{color_text, danger_badge, format_error};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+pub struct Rule {
+    pattern: Regex,
+    explanation: String,
+}
+
+// (pattern, explanation)
+const BUILT_IN_RULES: &[(&str, &str)] = &[
+    (
+        r"\brm\s+(-\S+\s+)*-\S*[rR]\S*\s+(-\S+\s+)*(/|/\*|~/?|\$HOME/?|\*|\.\*|\./?|\./\*)(\s|;|$)",
+        "Recursively deletes the root directory, your home directory or everything in the current directory.",
+    ),
+    (
+        r"--no-preserve-root",
+        "Allows rm to delete the whole filesystem.",
+    ),
+    (
+        r"\bdd\b.*\bof=/dev/",
+        "Writes raw data over a device, destroying whatever is stored on it.",
+    ),
+    (
+        r">\s*/dev/(sd|hd|vd|xvd|nvme|disk|mmcblk)",
+        "Writes over a disk device, destroying whatever is stored on it.",
+    ),
+    (
+        r"\bmkfs(\.\w+)?\b",
+        "Formats a device with a new filesystem, erasing everything on it.",
+    ),
+    (
+        r"\bchmod\s+(-\S+\s+)*-\S*R\S*\s+(-\S+\s+)*(0?777|a\+rwx)\b|\bchmod\s+(0?777|a\+rwx)\s+-\S*R",
+        "Makes every file under the directory readable, writable and executable by every user.",
+    ),
+    (
+        r"\b(curl|wget)\b[^|]*\|\s*(sudo\s+)?(ba|z|k|da|fi)?sh\b",
+        "Runs a script downloaded from the internet without showing it to you first.",
+    ),
+    (
+        r"\bgit\s+push\b.*(\s--force(\s|$)|\s-f\b|\s\+\S)",
+        "Force pushing overwrites the remote branch, commits other people pushed can be lost.",
+    ),
+    (
+        r"(?i)\bdrop\s+(table|database|schema)\b",
+        "Deletes the table or database and all of its data.",
+    ),
+    (
+        r"(?i)\btruncate\s+table\b",
+        "Deletes every row in the table.",
+    ),
+    (
+        r":\(\)\s*\{\s*:\s*\|\s*:\s*&\s*\}\s*;\s*:",
+        "A fork bomb, it starts processes until the machine stops responding.",
+    ),
+];
+
+lazy_static! {
+    static ref RULES: Vec<Rule> = load_rules();
+}
+
+// Built-in rules followed by the ones from the config file. A config rule with a broken pattern is reported and skipped.
+fn load_rules() -> Vec<Rule> {
+    let mut rules: Vec<Rule> = BUILT_IN_RULES
+        .iter()
+        .map(|(pattern, explanation)| Rule {
+            pattern: Regex::new(pattern).unwrap(),
+            explanation: explanation.to_string(),
+        })
+        .collect();
+
+    for rule in &config().danger_rules {
+        match Regex::new(&rule.pattern) {
+            Ok(pattern) => rules.push(Rule {
+                pattern,
+                explanation: if rule.explanation.is_empty() {
+                    format!("Matches the rule {}", rule.pattern)
+                } else {
+                    rule.explanation.clone()
+                },
+            }),
+            Err(e) => eprintln!(
+                "{}",
+                format_error(&format!("Ignoring danger rule '{}': {}", rule.pattern, e))
+            ),
+        }
+    }
+
+    rules
+}
+
+// The explanations of every rule the command matches
+pub fn check(command: &str) -> Vec<&'static str> {
+    RULES
+        .iter()
+        .filter(|rule| rule.pattern.is_match(command))
+        .map(|rule| rule.explanation.as_str())
+        .collect()
+}
+
+// Lists the steps whose commands match a danger rule, below the response so they can't be missed
+pub fn print_step_warnings(response: &str) {
+    for step in extract_steps(response) {
+        let explanations = check(&step.command);
+        if explanations.is_empty() {
+            continue;
+        }
+
+        println!(
+            "\n{} step {}: {}",
+            danger_badge(),
+            step.number,
+            color_text(&step.command, "cyan")
+        );
+        for explanation in explanations {
+            println!("  {}", explanation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_recursive_deletes_of_whole_directories() {
+        for command in [
+            "rm -rf /",
+            "sudo rm -rf ~",
+            "rm -r -f $HOME/",
+            "rm -rf *",
+            "rm -rf .",
+            "rm -rf ./",
+            "rm -fr ./*",
+            "rm -rf . && ls",
+        ] {
+            assert!(!check(command).is_empty(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn leaves_ordinary_deletes_alone() {
+        for command in [
+            "rm -rf ./build",
+            "rm -rf .cache",
+            "rm -r target/debug",
+            "rm ./notes.txt",
+        ] {
+            assert!(check(command).is_empty(), "{}", command);
+        }
+    }
+}
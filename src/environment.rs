@@ -0,0 +1,140 @@
+// src/environment.rs
+
+// Works out what machine the commands are for: the shell, the OS and Linux distribution, and which
+// package managers are installed. Command mode tells the model about it so it doesn't answer with brew
+// on Linux or bash syntax for fish users.
+//
+// Detection can be turned off or corrected in config.json:
+// { "environment": { "detect": true, "shell": "fish", "os": "macOS 14", "package_managers": ["brew"] } }
+
+use crate::config::config;
+use lazy_static::lazy_static;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Checked in this order, the first ones found are the system's own
+const PACKAGE_MANAGERS: &[&str] = &[
+    "apt",
+    "dnf",
+    "yum",
+    "pacman",
+    "zypper",
+    "apk",
+    "emerge",
+    "xbps-install",
+    "brew",
+    "port",
+    "nix",
+    "snap",
+    "flatpak",
+    "winget",
+    "choco",
+    "scoop",
+];
+
+pub struct Environment {
+    pub shell: Option<String>,
+    pub os: String,
+    pub package_managers: Vec<String>,
+}
+
+lazy_static! {
+    static ref CONTEXT: Option<String> = build_context();
+}
+
+// A sentence for the model describing the environment, None when detection is turned off
+pub fn context() -> Option<&'static str> {
+    CONTEXT.as_deref()
+}
+
+fn build_context() -> Option<String> {
+    let settings = &config().environment;
+    if !settings.detect {
+        return None;
+    }
+
+    // Values from the config win over what was detected
+    let detected = detect();
+    let environment = Environment {
+        shell: settings.shell.clone().or(detected.shell),
+        os: settings.os.clone().unwrap_or(detected.os),
+        package_managers: settings
+            .package_managers
+            .clone()
+            .unwrap_or(detected.package_managers),
+    };
+
+    Some(describe(&environment))
+}
+
+pub fn describe(environment: &Environment) -> String {
+    let mut text = format!("The user is on {}", environment.os);
+    if let Some(shell) = &environment.shell {
+        text.push_str(&format!(" using the {} shell", shell));
+    }
+    text.push('.');
+
+    if !environment.package_managers.is_empty() {
+        text.push_str(&format!(
+            " Installed package managers: {}.",
+            environment.package_managers.join(", ")
+        ));
+    }
+    text.push_str(" Give commands that work in this environment and shell.");
+    text
+}
+
+pub fn detect() -> Environment {
+    Environment {
+        shell: detect_shell(),
+        os: detect_os(),
+        package_managers: PACKAGE_MANAGERS
+            .iter()
+            .filter(|name| on_path(name))
+            .map(|name| name.to_string())
+            .collect(),
+    }
+}
+
+// The login shell's name, e.g. "zsh" from /bin/zsh
+pub fn detect_shell() -> Option<String> {
+    let shell = env::var("SHELL").ok()?;
+    let name = Path::new(&shell).file_name()?.to_str()?;
+    Some(name.to_string())
+}
+
+// "Ubuntu 22.04.4 LTS (Linux)", "macOS", "Windows" and so on
+fn detect_os() -> String {
+    match env::consts::OS {
+        "linux" => match linux_distribution() {
+            Some(distribution) => format!("{} (Linux)", distribution),
+            None => "Linux".to_string(),
+        },
+        "macos" => "macOS".to_string(),
+        "windows" => "Windows".to_string(),
+        other => other.to_string(),
+    }
+}
+
+// PRETTY_NAME from /etc/os-release, falling back to NAME
+fn linux_distribution() -> Option<String> {
+    let release = fs::read_to_string("/etc/os-release").ok()?;
+    let field = |key: &str| {
+        release.lines().find_map(|line| {
+            let value = line.strip_prefix(key)?.strip_prefix('=')?;
+            Some(value.trim().trim_matches('"').to_string())
+        })
+    };
+
+    field("PRETTY_NAME")
+        .or_else(|| field("NAME"))
+        .filter(|name| !name.is_empty())
+}
+
+pub fn on_path(program: &str) -> bool {
+    let Some(path) = env::var_os("PATH") else {
+        return false;
+    };
+    env::split_paths(&path).any(|dir| dir.join(program).is_file())
+}
@@ -0,0 +1,342 @@
+// src/file_edits/apply.rs
+
+// Works out what an edit does to its file before anything is written.
+//
+// Every change is looked up by its old lines, ignoring trailing whitespace. Line numbers from a diff are
+// only used to choose between several identical matches, since models rarely get them exactly right.
+// A change whose lines aren't in the file, that matches more than one place, or that overlaps another
+// change is a conflict, and a file with a conflict is left alone.
+
+use super::{Change, FileEdit};
+use std::fs;
+use std::path::Path;
+
+pub struct PlannedEdit {
+    // The attached path the edit applies to
+    pub path: String,
+    // None when the file doesn't exist yet
+    pub original: Option<String>,
+    pub updated: String,
+    // The changes as a unified diff, for the preview
+    pub preview: String,
+    pub conflicts: Vec<String>,
+}
+
+pub fn plan(edit: &FileEdit, attachments: &[String]) -> Result<PlannedEdit, String> {
+    let path = attached_path(edit.path.as_deref(), attachments)?;
+    let original = fs::read_to_string(&path).ok();
+    Ok(plan_text(path, original, &edit.changes))
+}
+
+fn plan_text(path: String, original: Option<String>, changes: &[Change]) -> PlannedEdit {
+    let text = original.as_deref().unwrap_or("");
+    let lines: Vec<&str> = text.lines().collect();
+    // lines() drops the \r of a CRLF file, the file is written back with the ending it had
+    let ending = match text.find('\n') {
+        Some(end) if text[..end].ends_with('\r') => "\r\n",
+        _ => "\n",
+    };
+
+    let mut conflicts = Vec::new();
+    let mut placed: Vec<(usize, &Change)> = Vec::new();
+    for (number, change) in changes.iter().enumerate() {
+        match locate(&lines, change) {
+            Ok(start) => placed.push((start, change)),
+            Err(reason) => conflicts.push(format!("Change {}: {}", number + 1, reason)),
+        }
+    }
+
+    placed.sort_by_key(|(start, _)| *start);
+    for pair in placed.windows(2) {
+        let (start, change) = pair[0];
+        if pair[1].0 < start + change.old.len() {
+            conflicts.push(format!("Two changes overlap at line {}", pair[1].0 + 1));
+        }
+    }
+
+    // The new text and a hunk per change, with line numbers shifted by the changes above it
+    let mut updated: Vec<&str> = Vec::new();
+    let mut preview = format!(
+        "--- {}\n+++ b/{}\n",
+        if original.is_some() {
+            format!("a/{}", path)
+        } else {
+            "/dev/null".to_string()
+        },
+        path
+    );
+    let mut cursor = 0;
+    for (start, change) in &placed {
+        if *start < cursor {
+            continue;
+        }
+        updated.extend(&lines[cursor..*start]);
+        let new_start = updated.len();
+        updated.extend(change.new.iter().map(String::as_str));
+        cursor = start + change.old.len();
+
+        preview.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            start + 1,
+            change.old.len(),
+            new_start + 1,
+            change.new.len()
+        ));
+        for line in line_diff(&change.old, &change.new) {
+            preview.push_str(&line);
+            preview.push('\n');
+        }
+    }
+    updated.extend(&lines[cursor.min(lines.len())..]);
+
+    let mut updated = updated.join(ending);
+    if !updated.is_empty() && (original.is_none() || text.ends_with('\n')) {
+        updated.push_str(ending);
+    }
+
+    PlannedEdit {
+        path,
+        original,
+        updated,
+        preview,
+        conflicts,
+    }
+}
+
+// The attached file an edit is for. Paths are compared by their trailing components, so `main.rs`
+// finds an attached `src/main.rs` and the other way around.
+fn attached_path(path: Option<&str>, attachments: &[String]) -> Result<String, String> {
+    if attachments.is_empty() {
+        return Err(
+            "No files are attached, use /attach <file> before asking for changes".to_string(),
+        );
+    }
+
+    let Some(path) = path else {
+        return match attachments {
+            [only] => Ok(only.clone()),
+            _ => Err(format!(
+                "The response doesn't say which file to change, and {} files are attached",
+                attachments.len()
+            )),
+        };
+    };
+
+    let wanted = Path::new(path.trim_start_matches("./"));
+    let matches: Vec<&String> = attachments
+        .iter()
+        .filter(|attached| {
+            let attached = Path::new(attached.trim_start_matches("./"));
+            attached == wanted || attached.ends_with(wanted) || wanted.ends_with(attached)
+        })
+        .collect();
+
+    match matches.as_slice() {
+        [only] => Ok(only.to_string()),
+        [] => Err(format!(
+            "The response changes {}, which isn't attached. Only attached files are changed.",
+            path
+        )),
+        _ => Err(format!("{} matches more than one attached file", path)),
+    }
+}
+
+// The index of the first line the change replaces
+fn locate(lines: &[&str], change: &Change) -> Result<usize, String> {
+    if change.old.is_empty() {
+        // Nothing to find: a new file, or lines added after line N in a diff
+        return match change.line {
+            _ if lines.is_empty() => Ok(0),
+            Some(line) => Ok(line.min(lines.len())),
+            None => Err("it doesn't say where in the file to add the new lines".to_string()),
+        };
+    }
+
+    let matches: Vec<usize> = (0..(lines.len() + 1).saturating_sub(change.old.len()))
+        .filter(|&start| {
+            change
+                .old
+                .iter()
+                .zip(&lines[start..])
+                .all(|(old, line)| old.trim_end() == line.trim_end())
+        })
+        .collect();
+
+    let first_line = change
+        .old
+        .iter()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+    match (matches.as_slice(), change.line) {
+        ([], _) => Err(format!(
+            "the lines starting with `{}` are not in the file, it may have changed",
+            first_line
+        )),
+        ([only], _) => Ok(*only),
+        (_, Some(line)) => Ok(*matches
+            .iter()
+            .min_by_key(|start| start.abs_diff(line.saturating_sub(1)))
+            .unwrap_or(&matches[0])),
+        (_, None) => Err(format!(
+            "the lines starting with `{}` appear {} times, it needs more context to pick one",
+            first_line,
+            matches.len()
+        )),
+    }
+}
+
+// The old lines turned into the new ones, as diff lines with " ", "-" and "+" markers
+fn line_diff(old: &[String], new: &[String]) -> Vec<String> {
+    // Longest common subsequence, the hunks are small enough for the table
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i].trim_end() == new[j].trim_end() {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i].trim_end() == new[j].trim_end() {
+            lines.push(format!(" {}", new[j]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(format!("-{}", old[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+{}", new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(old: &[&str], new: &[&str]) -> Change {
+        Change {
+            line: None,
+            old: old.iter().map(|line| line.to_string()).collect(),
+            new: new.iter().map(|line| line.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let original = "fn main() {\r\n    println!(\"a\");\r\n}\r\n".to_string();
+        let edit = change(&["    println!(\"a\");"], &["    println!(\"b\");"]);
+
+        let planned = plan_text("main.rs".to_string(), Some(original), &[edit]);
+        assert!(planned.conflicts.is_empty());
+        assert_eq!(
+            planned.updated,
+            "fn main() {\r\n    println!(\"b\");\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn keeps_lf_line_endings_and_a_missing_final_newline() {
+        let original = "a\nb\nc".to_string();
+        let planned = plan_text(
+            "f.txt".to_string(),
+            Some(original),
+            &[change(&["b"], &["B"])],
+        );
+        assert_eq!(planned.updated, "a\nB\nc");
+    }
+
+    fn at(line: usize, change: Change) -> Change {
+        Change {
+            line: Some(line),
+            ..change
+        }
+    }
+
+    #[test]
+    fn locate_ignores_trailing_whitespace() {
+        let lines = ["a  ", "b\t", "c"];
+        assert_eq!(locate(&lines, &change(&["a", "b   "], &[])), Ok(0));
+    }
+
+    #[test]
+    fn locate_reports_missing_lines() {
+        let lines = ["a", "b"];
+        let error = locate(&lines, &change(&["", "x"], &[])).unwrap_err();
+        assert!(error.contains("`x` are not in the file"), "{}", error);
+    }
+
+    #[test]
+    fn locate_needs_a_line_number_for_ambiguous_matches() {
+        let lines = ["x", "a", "x", "b", "x"];
+        let error = locate(&lines, &change(&["x"], &[])).unwrap_err();
+        assert!(error.contains("appear 3 times"), "{}", error);
+
+        // The closest match to the hunk's line wins
+        assert_eq!(locate(&lines, &at(4, change(&["x"], &[]))), Ok(2));
+        assert_eq!(locate(&lines, &at(9, change(&["x"], &[]))), Ok(4));
+    }
+
+    #[test]
+    fn locate_places_insertions_by_line_number() {
+        let lines = ["a", "b", "c"];
+        assert_eq!(locate(&lines, &at(2, change(&[], &["new"]))), Ok(2));
+        assert_eq!(locate(&lines, &at(10, change(&[], &["new"]))), Ok(3));
+        assert!(locate(&lines, &change(&[], &["new"])).is_err());
+        // A new file has only one place to put them
+        assert_eq!(locate(&[], &change(&[], &["new"])), Ok(0));
+    }
+
+    #[test]
+    fn inserts_lines_after_the_given_line() {
+        let planned = plan_text(
+            "f.txt".to_string(),
+            Some("a\nb\n".to_string()),
+            &[at(1, change(&[], &["between"]))],
+        );
+        assert!(planned.conflicts.is_empty());
+        assert_eq!(planned.updated, "a\nbetween\nb\n");
+    }
+
+    #[test]
+    fn creates_missing_files() {
+        let planned = plan_text("new.txt".to_string(), None, &[change(&[], &["hello"])]);
+        assert_eq!(planned.updated, "hello\n");
+        assert!(planned
+            .preview
+            .starts_with("--- /dev/null\n+++ b/new.txt\n"));
+    }
+
+    #[test]
+    fn overlapping_changes_conflict() {
+        let planned = plan_text(
+            "f.txt".to_string(),
+            Some("a\nb\nc\n".to_string()),
+            &[change(&["a", "b"], &["A"]), change(&["b", "c"], &["C"])],
+        );
+        assert_eq!(planned.conflicts, vec!["Two changes overlap at line 2"]);
+    }
+
+    #[test]
+    fn applies_changes_in_file_order_and_matches_trailing_blank_lines() {
+        let planned = plan_text(
+            "f.txt".to_string(),
+            Some("one\ntwo\n\nthree\n\n".to_string()),
+            &[change(&["three", ""], &["3"]), change(&["one"], &["1"])],
+        );
+        assert!(planned.conflicts.is_empty());
+        assert_eq!(planned.updated, "1\ntwo\n\n3\n");
+        assert_eq!(
+            planned.preview,
+            "--- a/f.txt\n+++ b/f.txt\n@@ -1,1 +1,1 @@\n-one\n+1\n@@ -4,2 +4,1 @@\n-three\n-\n+3\n"
+        );
+    }
+}
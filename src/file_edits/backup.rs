@@ -0,0 +1,127 @@
+// src/file_edits/backup.rs
+
+// Copies of files taken before /apply writes them, so /apply undo can put them back.
+//
+// Each /apply gets a directory under $CONSOLE_AI_CHAT_HOME/backups/<session id>/ holding the original of every
+// file it changed, the version it wrote, and a manifest.json listing them. Undo restores the newest one and
+// deletes it, so undoing again goes one /apply further back.
+
+use super::apply::PlannedEdit;
+use crate::session::store::data_dir;
+use crate::session::timestamp;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    // Absolute, so undo works from another directory
+    path: String,
+    // The backup of the file as it was, None if /apply created it
+    original: Option<String>,
+    // The copy of what /apply wrote, to tell if the file was changed again since
+    applied: String,
+}
+
+pub struct Backup {
+    dir: PathBuf,
+    entries: Vec<Entry>,
+}
+
+fn session_dir(session_id: &str) -> PathBuf {
+    data_dir().join("backups").join(session_id)
+}
+
+pub fn save(session_id: &str, edits: &[PlannedEdit]) -> io::Result<()> {
+    let dir = session_dir(session_id).join(format!(
+        "{}-{:09}",
+        timestamp::now(),
+        timestamp::subsec_nanos()
+    ));
+    fs::create_dir_all(&dir)?;
+
+    let mut entries = Vec::new();
+    for (number, edit) in edits.iter().enumerate() {
+        let original = match &edit.original {
+            Some(text) => {
+                let name = format!("{}.orig", number);
+                fs::write(dir.join(&name), text)?;
+                Some(name)
+            }
+            None => None,
+        };
+        let applied = format!("{}.new", number);
+        fs::write(dir.join(&applied), &edit.updated)?;
+
+        entries.push(Entry {
+            path: absolute(&edit.path).display().to_string(),
+            original,
+            applied,
+        });
+    }
+
+    let manifest = serde_json::to_string_pretty(&entries).map_err(io::Error::other)?;
+    fs::write(dir.join("manifest.json"), manifest)
+}
+
+// The most recent /apply of the session that hasn't been undone
+pub fn latest(session_id: &str) -> Option<Backup> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(session_dir(session_id))
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.join("manifest.json").is_file())
+        .collect();
+    dirs.sort();
+
+    let dir = dirs.pop()?;
+    let manifest = fs::read_to_string(dir.join("manifest.json")).ok()?;
+    let entries = serde_json::from_str(&manifest).ok()?;
+    Some(Backup { dir, entries })
+}
+
+impl Backup {
+    pub fn paths(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect()
+    }
+
+    // Files that no longer hold what /apply wrote, undoing would throw those changes away
+    pub fn changed_since(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|entry| {
+                let applied = fs::read_to_string(self.dir.join(&entry.applied)).ok();
+                applied.is_none() || fs::read_to_string(&entry.path).ok() != applied
+            })
+            .map(|entry| entry.path.as_str())
+            .collect()
+    }
+
+    // Puts every file back as it was, removing the ones /apply created, then drops the backup
+    pub fn restore(self) -> io::Result<()> {
+        for entry in &self.entries {
+            match &entry.original {
+                Some(name) => fs::write(&entry.path, fs::read(self.dir.join(name))?)?,
+                None => match fs::remove_file(&entry.path) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                    _ => {}
+                },
+            }
+        }
+        fs::remove_dir_all(&self.dir)
+    }
+}
+
+fn absolute(path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    env::current_dir()
+        .map(|dir| dir.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
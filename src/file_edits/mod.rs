@@ -0,0 +1,83 @@
+// src/file_edits/mod.rs
+
+// Changes to attached files proposed by the model, for /attach and /apply in a conversation.
+//
+// Files attached with /attach are sent with the next prompt and remembered in the session. The model can be
+// asked to answer with a unified diff (the diff style, -d) or search/replace blocks (the replace style, -r).
+// Both are parsed into the same shape, a list of changes per file that each swap some lines for others, and
+// only applied to files that were attached. A copy of every file is kept before it's written, for /apply undo.
+
+pub mod apply;
+pub mod backup;
+pub mod parse;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct FileEdit {
+    // None when the response didn't say, which is fine with a single attached file
+    pub path: Option<String>,
+    pub changes: Vec<Change>,
+}
+
+// Replaces the `old` lines with the `new` ones. An empty `old` creates the file or inserts at `line`.
+#[derive(Debug, Clone)]
+pub struct Change {
+    // Where a diff hunk says the lines are, 1-based. Only used to pick between identical matches.
+    pub line: Option<usize>,
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+}
+
+// The prompt with each attached file in front of it, in a fence tagged with the file's language
+pub fn attachment_prompt(paths: &[String], prompt: &str) -> io::Result<String> {
+    let mut text = String::new();
+    for path in paths {
+        let content = fs::read_to_string(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("Could not read {}: {}", path, e)))?;
+        let fence = if content.contains("```") {
+            "````"
+        } else {
+            "```"
+        };
+
+        text.push_str(&format!(
+            "File {}:\n{}{}\n{}",
+            path,
+            fence,
+            language_for_path(path),
+            content
+        ));
+        if !content.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(fence);
+        text.push_str("\n\n");
+    }
+    text.push_str(prompt);
+    Ok(text)
+}
+
+// The highlighter's name for a file's language, from its extension
+pub fn language_for_path(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "py" => "python",
+        "sh" | "bash" => "bash",
+        "rs" => "rust",
+        "js" | "mjs" | "cjs" | "jsx" => "javascript",
+        "ts" | "tsx" => "typescript",
+        "cs" => "csharp",
+        "css" => "css",
+        "html" | "htm" => "html",
+        "sql" => "sql",
+        "swift" => "swift",
+        _ => "",
+    }
+}
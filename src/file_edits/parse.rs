@@ -0,0 +1,297 @@
+// src/file_edits/parse.rs
+
+// Reads the file changes out of a response. Unified diffs and search/replace blocks are both recognized,
+// inside code fences or not, and can be mixed in one response.
+//
+// A diff names its file in the --- and +++ lines. Search/replace blocks belong to the last line before them
+// that looks like a path, such as `src/main.rs` or **src/main.rs** on its own.
+
+use super::{Change, FileEdit};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    // Only the old side's start line is needed, the counts are worked out from the lines themselves
+    static ref HUNK_HEADER: Regex = Regex::new(r"^@@ -(\d+)(?:,\d+)? \+\d+(?:,\d+)? @@").unwrap();
+}
+
+pub fn parse_edits(response: &str) -> Vec<FileEdit> {
+    let lines: Vec<&str> = response.lines().collect();
+    let mut edits: Vec<FileEdit> = Vec::new();
+    let mut path: Option<String> = None;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+
+        if is_diff_header(&lines, i) {
+            path = diff_path(line, lines[i + 1]);
+            i += 2;
+            continue;
+        }
+
+        if let Some(caps) = HUNK_HEADER.captures(line) {
+            let start = caps[1].parse().unwrap_or(0);
+            let (change, next) = read_hunk(&lines, i + 1, start);
+            push_change(&mut edits, path.clone(), change);
+            i = next;
+            continue;
+        }
+
+        if is_marker(line, "<<<<<<<", "SEARCH") {
+            if let Some((change, next)) = read_search_replace(&lines, i + 1) {
+                push_change(&mut edits, path.clone(), change);
+                i = next;
+                continue;
+            }
+        }
+
+        if let Some(candidate) = path_line(line) {
+            path = Some(candidate);
+        }
+        i += 1;
+    }
+
+    edits
+}
+
+fn is_diff_header(lines: &[&str], i: usize) -> bool {
+    lines[i].starts_with("--- ")
+        && lines
+            .get(i + 1)
+            .is_some_and(|next| next.starts_with("+++ "))
+}
+
+// The new side's path, or the old one's when the file is deleted. Git's a/ and b/ prefixes and
+// timestamps after a tab are dropped.
+fn diff_path(old: &str, new: &str) -> Option<String> {
+    let clean = |line: &str, prefix: &str| {
+        let path = line[4..].split('\t').next().unwrap_or("").trim();
+        let path = path.strip_prefix(prefix).unwrap_or(path);
+        (!path.is_empty() && path != "/dev/null").then(|| path.to_string())
+    };
+    clean(new, "b/").or_else(|| clean(old, "a/"))
+}
+
+// The lines of one hunk, up to the next hunk, file header or anything that isn't part of a diff
+fn read_hunk(lines: &[&str], from: usize, start: usize) -> (Change, usize) {
+    let mut change = Change {
+        line: Some(start),
+        old: Vec::new(),
+        new: Vec::new(),
+    };
+    // Models often drop the space in front of empty context lines, but a blank line at the end is
+    // more likely the gap before the closing fence
+    let mut trailing_blank = 0;
+    let mut i = from;
+
+    while i < lines.len() {
+        let line = lines[i];
+        if line.starts_with("@@") || is_diff_header(lines, i) {
+            break;
+        }
+
+        match line.chars().next() {
+            None => {
+                change.old.push(String::new());
+                change.new.push(String::new());
+                trailing_blank += 1;
+                i += 1;
+                continue;
+            }
+            Some(' ') => {
+                change.old.push(line[1..].to_string());
+                change.new.push(line[1..].to_string());
+            }
+            Some('-') => change.old.push(line[1..].to_string()),
+            Some('+') => change.new.push(line[1..].to_string()),
+            // "\ No newline at end of file"
+            Some('\\') => {}
+            _ => break,
+        }
+        trailing_blank = 0;
+        i += 1;
+    }
+
+    for _ in 0..trailing_blank {
+        change.old.pop();
+        change.new.pop();
+    }
+    (change, i)
+}
+
+// The lines between the markers, or None when the block isn't closed
+fn read_search_replace(lines: &[&str], from: usize) -> Option<(Change, usize)> {
+    let divider = (from..lines.len()).find(|&i| {
+        let line = lines[i].trim();
+        line.len() >= 7 && line.chars().all(|c| c == '=')
+    })?;
+    let end = (divider + 1..lines.len()).find(|&i| is_marker(lines[i], ">>>>>>>", "REPLACE"))?;
+
+    let change = Change {
+        line: None,
+        old: lines[from..divider]
+            .iter()
+            .map(|line| line.to_string())
+            .collect(),
+        new: lines[divider + 1..end]
+            .iter()
+            .map(|line| line.to_string())
+            .collect(),
+    };
+    Some((change, end + 1))
+}
+
+fn is_marker(line: &str, prefix: &str, word: &str) -> bool {
+    let line = line.trim();
+    line.starts_with(prefix) && line.contains(word)
+}
+
+// A line holding nothing but a file path, possibly in backticks or bold, or followed by a colon
+fn path_line(line: &str) -> Option<String> {
+    if line.trim_start().starts_with("```") {
+        return None;
+    }
+
+    let path = line
+        .trim()
+        .trim_end_matches(':')
+        .trim_matches(|c| c == '`' || c == '*')
+        .trim();
+    let looks_like_path = !path.is_empty()
+        && !path.contains(char::is_whitespace)
+        && (path.contains('/') || path.contains('.'))
+        && !path.ends_with('.')
+        && !path.starts_with(['-', '+', '<', '>', '=', '#']);
+
+    looks_like_path.then(|| path.to_string())
+}
+
+// Changes to the same file are kept together, in the order they appear
+fn push_change(edits: &mut Vec<FileEdit>, path: Option<String>, change: Change) {
+    match edits.iter_mut().find(|edit| edit.path == path) {
+        Some(edit) => edit.changes.push(change),
+        None => edits.push(FileEdit {
+            path,
+            changes: vec![change],
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_a_unified_diff() {
+        let response = "Here's the fix:\n\
+                        ```diff\n\
+                        --- a/src/main.rs\n\
+                        +++ b/src/main.rs\n\
+                        @@ -3,3 +3,3 @@ fn main() {\n\
+                        \x20    let a = 1;\n\
+                        -    let b = 2;\n\
+                        +    let b = 3;\n\
+                        \x20    println!(\"{}\", a + b);\n\
+                        ```\n";
+        let edits = parse_edits(response);
+
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].path.as_deref(), Some("src/main.rs"));
+        let change = &edits[0].changes[0];
+        assert_eq!(change.line, Some(3));
+        assert_eq!(
+            change.old,
+            lines(&[
+                "    let a = 1;",
+                "    let b = 2;",
+                "    println!(\"{}\", a + b);"
+            ])
+        );
+        assert_eq!(
+            change.new,
+            lines(&[
+                "    let a = 1;",
+                "    let b = 3;",
+                "    println!(\"{}\", a + b);"
+            ])
+        );
+    }
+
+    #[test]
+    fn keeps_blank_context_lines_but_drops_trailing_ones() {
+        let response =
+            "--- a/f.py\n+++ b/f.py\n@@ -1,3 +1,3 @@\n-a = 1\n+a = 2\n\n b = 2\n\n\n```\n";
+        let change = &parse_edits(response)[0].changes[0];
+
+        assert_eq!(change.old, lines(&["a = 1", "", "b = 2"]));
+        assert_eq!(change.new, lines(&["a = 2", "", "b = 2"]));
+    }
+
+    #[test]
+    fn splits_hunks_and_ignores_no_newline_markers() {
+        let response = "--- a/f.txt\n+++ b/f.txt\n@@ -1 +1 @@\n-one\n+ONE\n@@ -9 +9 @@\n-nine\n\\ No newline at end of file\n+NINE\n";
+        let edits = parse_edits(response);
+
+        assert_eq!(edits[0].changes.len(), 2);
+        assert_eq!(edits[0].changes[1].line, Some(9));
+        assert_eq!(edits[0].changes[1].old, lines(&["nine"]));
+        assert_eq!(edits[0].changes[1].new, lines(&["NINE"]));
+    }
+
+    #[test]
+    fn uses_the_old_path_for_deleted_files() {
+        let response = "--- a/old.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-gone\n";
+        assert_eq!(parse_edits(response)[0].path.as_deref(), Some("old.txt"));
+    }
+
+    #[test]
+    fn parses_search_replace_blocks_under_their_path() {
+        let response = "**src/lib.rs**\n\
+                        ```rust\n\
+                        <<<<<<< SEARCH\n\
+                        fn a() {}\n\
+                        =======\n\
+                        fn a() -> u8 { 1 }\n\
+                        >>>>>>> REPLACE\n\
+                        ```\n\
+                        `src/other.rs`:\n\
+                        <<<<<<< SEARCH\n\
+                        x\n\
+                        =======\n\
+                        y\n\
+                        >>>>>>> REPLACE\n\
+                        And in src/lib.rs again:\n\
+                        src/lib.rs\n\
+                        <<<<<<< SEARCH\n\
+                        fn b() {}\n\
+                        =======\n\
+                        >>>>>>> REPLACE\n";
+        let edits = parse_edits(response);
+
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].path.as_deref(), Some("src/lib.rs"));
+        assert_eq!(edits[0].changes.len(), 2);
+        assert_eq!(edits[0].changes[0].line, None);
+        assert_eq!(edits[0].changes[0].old, lines(&["fn a() {}"]));
+        assert_eq!(edits[0].changes[0].new, lines(&["fn a() -> u8 { 1 }"]));
+        assert!(edits[0].changes[1].new.is_empty());
+        assert_eq!(edits[1].path.as_deref(), Some("src/other.rs"));
+    }
+
+    #[test]
+    fn skips_unclosed_search_blocks() {
+        let response = "<<<<<<< SEARCH\nx\n=======\ny\n";
+        assert!(parse_edits(response).is_empty());
+    }
+
+    #[test]
+    fn a_block_without_a_path_has_none() {
+        let response = "Change this:\n<<<<<<< SEARCH\nx\n=======\ny\n>>>>>>> REPLACE\n";
+        assert_eq!(parse_edits(response)[0].path, None);
+    }
+}
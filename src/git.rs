@@ -0,0 +1,164 @@
+// src/git.rs
+
+// Runs git for the `commit` and `review` subcommands, and splits its diffs into files and hunks.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// A file in a diff and its hunks, each starting with its @@ line
+pub struct DiffFile {
+    pub path: String,
+    pub hunks: Vec<String>,
+}
+
+// Git's stdout, or what it printed to stderr when it fails
+pub fn git(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Could not run git: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+// `git diff` with the arguments, in the format split_diff expects whatever the user's config says.
+// diff.noprefix and diff.mnemonicPrefix change the a/ and b/ prefixes, color.diff adds escape codes.
+pub fn diff(args: &[&str]) -> Result<String, String> {
+    let mut full = vec![
+        "diff",
+        "--no-color",
+        "--no-ext-diff",
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+    ];
+    full.extend(args);
+    git(&full)
+}
+
+// Commits what's staged with the message, git's own output goes to the terminal
+pub fn commit(message: &str) -> Result<(), String> {
+    let mut child = Command::new("git")
+        .args(["commit", "--file", "-"])
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run git: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(message.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("git commit failed ({})", status))
+    }
+}
+
+pub fn split_diff(diff: &str) -> Vec<DiffFile> {
+    let mut files: Vec<DiffFile> = Vec::new();
+
+    for line in diff.lines() {
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            // Replaced by the +++ line below when there is one, binary files and renames don't always have it
+            let path = paths
+                .rsplit_once(" b/")
+                .map_or(paths, |(_, path)| path)
+                .to_string();
+            files.push(DiffFile {
+                path,
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if let Some(path) = line.strip_prefix("+++ b/") {
+            if file.hunks.is_empty() {
+                file.path = path.to_string();
+            }
+        }
+
+        if line.starts_with("@@") {
+            file.hunks.push(String::new());
+        }
+        if let Some(hunk) = file.hunks.last_mut() {
+            hunk.push_str(line);
+            hunk.push('\n');
+        }
+    }
+
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_files_and_hunks() {
+        let diff = "diff --git a/src/main.rs b/src/main.rs\n\
+                    index 1111111..2222222 100644\n\
+                    --- a/src/main.rs\n\
+                    +++ b/src/main.rs\n\
+                    @@ -1,2 +1,2 @@\n\
+                    -a\n\
+                    +b\n\
+                    \x20c\n\
+                    @@ -10 +10 @@ fn main() {\n\
+                    -x\n\
+                    +y\n\
+                    diff --git a/notes.txt b/notes.txt\n\
+                    new file mode 100644\n\
+                    --- /dev/null\n\
+                    +++ b/notes.txt\n\
+                    @@ -0,0 +1 @@\n\
+                    +hello\n";
+        let files = split_diff(diff);
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "src/main.rs");
+        assert_eq!(files[0].hunks.len(), 2);
+        assert_eq!(files[0].hunks[0], "@@ -1,2 +1,2 @@\n-a\n+b\n c\n");
+        assert!(files[0].hunks[1].starts_with("@@ -10 +10 @@ fn main() {\n"));
+        assert_eq!(files[1].path, "notes.txt");
+        assert_eq!(files[1].hunks, vec!["@@ -0,0 +1 @@\n+hello\n"]);
+    }
+
+    #[test]
+    fn a_line_starting_with_plus_plus_plus_inside_a_hunk_is_content() {
+        let diff = "diff --git a/f.txt b/f.txt\n\
+                    --- a/f.txt\n\
+                    +++ b/f.txt\n\
+                    @@ -1 +1 @@\n\
+                    -old\n\
+                    +++ b/other\n";
+        let files = split_diff(diff);
+
+        assert_eq!(files[0].path, "f.txt");
+        assert_eq!(files[0].hunks[0], "@@ -1 +1 @@\n-old\n+++ b/other\n");
+    }
+
+    #[test]
+    fn binary_files_and_renames_keep_the_header_path() {
+        let diff = "diff --git a/logo.png b/logo.png\n\
+                    Binary files a/logo.png and b/logo.png differ\n\
+                    diff --git a/old name.txt b/new name.txt\n\
+                    similarity index 100%\n\
+                    rename from old name.txt\n\
+                    rename to new name.txt\n";
+        let files = split_diff(diff);
+
+        assert_eq!(files[0].path, "logo.png");
+        assert!(files[0].hunks.is_empty());
+        assert_eq!(files[1].path, "new name.txt");
+    }
+}
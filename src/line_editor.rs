@@ -0,0 +1,231 @@
+// src/line_editor.rs
+
+// Reads prompts in the conversation loop with line editing instead of a bare `read_line`.
+// Arrow keys, Ctrl-R reverse search and the usual emacs bindings come from rustyline.
+// Prompts are kept in a history file so they can be recalled in later runs.
+//
+// Multi-line input: pasted text keeps its newlines (bracketed paste), Alt-Enter or Ctrl-J inserts a
+// newline, and Enter after a typed trailing backslash continues on the next line, dropping the backslash.
+// Enter submits.
+
+use crate::session::store::data_dir;
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::{
+    Cmd, ConditionalEventHandler, Config, Editor, Event, EventContext, EventHandler, KeyCode,
+    KeyEvent, Modifiers, RepeatCount,
+};
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+const HISTORY_SIZE: usize = 1000;
+
+pub struct LineEditor {
+    editor: Editor<(), DefaultHistory>,
+    history_path: PathBuf,
+    // The input as it was each time a backslash continued it, see ContinueLine
+    continued: Arc<Mutex<Vec<String>>>,
+}
+
+impl LineEditor {
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        let config = Config::builder()
+            .max_history_size(HISTORY_SIZE)?
+            .history_ignore_dups(true)?
+            .auto_add_history(false)
+            .bracketed_paste(true)
+            .build();
+
+        let continued = Arc::new(Mutex::new(Vec::new()));
+        let mut editor = Editor::with_config(config)?;
+        editor.bind_sequence(
+            KeyEvent(KeyCode::Enter, Modifiers::NONE),
+            EventHandler::Conditional(Box::new(ContinueLine {
+                continued: Arc::clone(&continued),
+            })),
+        );
+        editor.bind_sequence(KeyEvent(KeyCode::Enter, Modifiers::ALT), Cmd::Newline);
+        editor.bind_sequence(KeyEvent::ctrl('J'), Cmd::Newline);
+
+        // A missing history file just means this is the first run
+        let history_path = data_dir().join("prompt_history.txt");
+        let _ = editor.load_history(&history_path);
+
+        Ok(LineEditor {
+            editor,
+            history_path,
+            continued,
+        })
+    }
+
+    // Returns None at end of input (Ctrl-D). Ctrl-C discards the current line and returns an empty one.
+    pub fn read_prompt(&mut self, prompt: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        self.take_continued();
+        match self.editor.readline(prompt) {
+            Ok(line) => {
+                let line = strip_continuations(&line, &self.take_continued());
+                if !line.trim().is_empty() {
+                    self.editor.add_history_entry(line.as_str())?;
+                    self.save_history();
+                }
+                Ok(Some(line))
+            }
+            Err(ReadlineError::Interrupted) => Ok(Some(String::new())),
+            Err(ReadlineError::Eof) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn take_continued(&self) -> Vec<String> {
+        self.continued
+            .lock()
+            .map(|mut continued| std::mem::take(&mut *continued))
+            .unwrap_or_default()
+    }
+
+    fn save_history(&mut self) {
+        if let Some(dir) = self.history_path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        let _ = self.editor.save_history(&self.history_path);
+    }
+}
+
+// Enter at the end of input ending in a backslash starts a new line instead of submitting. The input at
+// that point is recorded, so the backslash can be dropped afterwards without touching backslashes that
+// were pasted, like the line continuations of a shell script.
+struct ContinueLine {
+    continued: Arc<Mutex<Vec<String>>>,
+}
+
+impl ConditionalEventHandler for ContinueLine {
+    fn handle(
+        &self,
+        _event: &Event,
+        _count: RepeatCount,
+        _positive: bool,
+        context: &EventContext,
+    ) -> Option<Cmd> {
+        let line = context.line();
+        if context.pos() != line.len() || !line.ends_with('\\') {
+            return None;
+        }
+        if let Ok(mut continued) = self.continued.lock() {
+            continued.push(line.to_string());
+        }
+        Some(Cmd::Newline)
+    }
+}
+
+// Removes the backslashes recorded by ContinueLine. One whose line was edited afterwards is left in place.
+fn strip_continuations(line: &str, continued: &[String]) -> String {
+    let mut positions: Vec<usize> = continued
+        .iter()
+        .filter(|before| {
+            line.strip_prefix(before.as_str())
+                .is_some_and(|rest| rest.starts_with('\n'))
+        })
+        .map(|before| before.len() - 1)
+        .collect();
+    positions.sort_unstable();
+    positions.dedup();
+
+    let mut line = line.to_string();
+    for position in positions.into_iter().rev() {
+        line.remove(position);
+    }
+    line
+}
+
+// Opens $VISUAL or $EDITOR (falling back to vi) on a temporary file and returns what was saved
+pub fn compose_in_editor(initial: &str) -> io::Result<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    let path = create_temp_file(initial)?;
+
+    // Run through the shell so editors configured with arguments, like "code --wait", work
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(&path)
+        .status();
+
+    let result = match status {
+        Ok(status) if status.success() => fs::read_to_string(&path),
+        Ok(status) => Err(io::Error::other(format!("{} exited with {}", editor, status))),
+        Err(e) => Err(e),
+    };
+
+    let _ = fs::remove_file(&path);
+    result
+}
+
+// A new file only this user can read, under a name nobody can guess and create first. create_new fails on
+// anything already there, a symlink included, and another name is tried.
+fn create_temp_file(content: &str) -> io::Result<PathBuf> {
+    let mut attempts = 0;
+    loop {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        let path = env::temp_dir().join(format!("console_ai_chat-{:016x}.md", hasher.finish()));
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        match options.open(&path) {
+            Ok(mut file) => {
+                if let Err(e) = file.write_all(content.as_bytes()) {
+                    let _ = fs::remove_file(&path);
+                    return Err(e);
+                }
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempts < 10 => attempts += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_only_recorded_continuations() {
+        // Typed "echo a \\", Enter, then pasted a script with its own continuation
+        let line = "echo a \\\nls -l \\\n  /tmp";
+        let continued = vec!["echo a \\".to_string()];
+        assert_eq!(
+            strip_continuations(line, &continued),
+            "echo a \nls -l \\\n  /tmp"
+        );
+    }
+
+    #[test]
+    fn strips_several_continuations() {
+        let line = "a\\\nb\\\nc";
+        let continued = vec!["a\\".to_string(), "a\\\nb\\".to_string()];
+        assert_eq!(strip_continuations(line, &continued), "a\nb\nc");
+    }
+
+    #[test]
+    fn keeps_backslashes_of_edited_lines() {
+        let line = "changed\\\nb";
+        let continued = vec!["a\\".to_string()];
+        assert_eq!(strip_continuations(line, &continued), line);
+    }
+}
@@ -1,18 +1,43 @@
 // main.rs
 
+mod clipboard;
+mod code_blocks;
+mod code_runner;
+mod commands;
+mod config;
 mod models;
 mod conversation;
+mod danger;
+mod environment;
+mod file_edits;
+mod git;
+mod line_editor;
+mod process;
+mod prompt;
 mod response_types;
+mod session;
+mod shell_history;
+mod slash_commands;
+mod step_runner;
+mod steps;
 mod syntax_highlighter;
 mod text_formatter;
 
-use conversation::continue_conversation;
+use clipboard::copy_to_clipboard;
+use code_blocks::{extract_code_blocks, save_all};
+use conversation::{continue_conversation, Conversation};
 use dotenv::dotenv;
 use models::{claude::Claude, gpt::GPT, AIModel};
-use response_types::{command::Command, short::Short, ResponseModifier};
+use prompt::ask_yes_no;
+use response_types::command::Command;
+use response_types::{all_modifiers, from_flag, ModifierStack, Renderer};
+use session::Session;
 use std::env;
+use std::fs;
 use std::io::{self};
-use text_formatter::{color_text, format_error, print_formatted_response};
+use step_runner::run_response_steps;
+use steps::{extract_steps, to_json, to_script};
+use text_formatter::format_error;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -20,20 +45,58 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
 
     // Get command line arguments, should have at least 2
-    let args: Vec<String> = env::args().collect();
-    
-    // Check if the user has provided the required arguments, -cl or -gpt is required, -s or -c is optional, and the prompt is required
+    let mut args: Vec<String> = env::args().collect();
+
+    // Subcommands such as `history` take over before the prompt arguments are parsed
+    if let Some(result) = commands::dispatch(&args[1..]).await {
+        if let Err(e) = result {
+            eprintln!("{}", format_error(&e.to_string()));
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if matches!(args.get(1).map(String::as_str), Some("--help" | "-h")) {
+        print_usage(&args[0]);
+        return Ok(());
+    }
+
+    // Output options can go anywhere after the model flag, take them out before the positional arguments are read
+    let emit_script = match take_option(&mut args, "--emit-script") {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("{}", format_error(&e));
+            std::process::exit(1);
+        }
+    };
+    let save_code = match take_option(&mut args, "--save-code") {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("{}", format_error(&e));
+            std::process::exit(1);
+        }
+    };
+    let emit_json = take_switch(&mut args, "--emit-json");
+    let run_steps = take_switch(&mut args, "--run");
+    let copy = take_switch(&mut args, "--copy");
+    let overwrite = take_switch(&mut args, "--overwrite");
+    let model_name = match take_option(&mut args, "--model") {
+        Ok(name) => name,
+        Err(e) => {
+            eprintln!("{}", format_error(&e));
+            std::process::exit(1);
+        }
+    };
+
+    // Check if the user has provided the required arguments, -cl or -gpt is required, modifier flags are optional, and the prompt is required
     if args.len() < 3 {
-        eprintln!(
-            "Usage: {} <-cl|-gpt> [-s|-c] \"your question in quotes\"",
-            args[0]
-        );
+        print_usage(&args[0]);
         std::process::exit(1);
     }
-    
+
     // Create a new instance of the model based on the user's choice, -cl for Claude, -gpt for ChatGPT
     let mut model: Box<dyn AIModel> = match args[1].as_str() {
-        "-cl" => Box::new(Claude),
+        "-cl" => Box::new(Claude::new()),
         "-gpt" => Box::new(GPT::new()),
         _ => {
             eprintln!("Invalid model specified. Use -cl for Claude or -gpt for GPT.");
@@ -41,49 +104,177 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    // Check if the user has provided a response modifier, -s for short response, -c for command response
-    let (response_modifier, prompt_index) = if args.len() >= 4 {
-        match args[2].as_str() {
-            "-s" => (Some(Box::new(Short) as Box<dyn ResponseModifier>), 3),
-            "-c" => (Some(Box::new(Command) as Box<dyn ResponseModifier>), 3),
-            _ => (None, 2),
-        }
+    // --model picks a model other than the provider's default, e.g. gpt-4o
+    if let Some(name) = &model_name {
+        model.set_model(name);
+    }
+
+    // Check if the user has provided response modifiers, -s for short response, -c for command response,
+    // or any flag declared in the config file. Several can be given and are applied in order, e.g. -s -c.
     // If the user has not provided a response modifier, then the default API response will be used
-    } else {
-        (None, 2)
-    };
+    let mut stack = ModifierStack::default();
+    let mut prompt_index = 2;
+    while prompt_index + 1 < args.len() {
+        match from_flag(&args[prompt_index]) {
+            Some(modifier) => stack.push(modifier),
+            None => break,
+        }
+        prompt_index += 1;
+    }
+    // Steps can only be pulled out of a command style response
+    if (emit_script.is_some() || emit_json || run_steps) && stack.renderer() != Renderer::Command {
+        stack.push(Box::new(Command));
+    }
+    let mode = stack.mode();
 
     let original_prompt = &args[prompt_index];
 
-    // Modify the prompt if a response modifier is provided
-    let modified_prompt = if let Some(modifier) = response_modifier {
-        modifier.modify_prompt(original_prompt)
-    } else {
-        original_prompt.to_string()
-    };
+    // Every conversation is saved so it can be found again with `history search`.
+    // The conversation applies the mode's response modifier to each prompt it sends.
+    let session = Session::new(model.provider(), model.model_name(), mode.as_deref());
+    let mut conversation = Conversation::new(model, session);
 
-    let is_command_mode = args.len() >= 4 && args[2] == "-c";
+    match conversation.send(original_prompt).await {
+        // Writing the steps out is a one-off, there's no conversation to continue
+        Ok(response) if emit_script.is_some() || emit_json => {
+            let steps = extract_steps(&response);
+            if emit_json {
+                println!("{}", to_json(&steps));
+            } else {
+                conversation.print_response(&response);
+            }
+            if copy {
+                copy_response(&response);
+            }
+
+            if let Some(path) = emit_script {
+                if steps.is_empty() {
+                    eprintln!("{}", format_error("No commands found in the response, nothing was written."));
+                    std::process::exit(1);
+                }
+                write_script(&path, &to_script(&steps, original_prompt))?;
+                eprintln!("\nWrote {} steps to {}", steps.len(), path);
+            }
+        }
+        // Also a one-off: show the response and write its code blocks out as files
+        Ok(response) if save_code.is_some() => {
+            conversation.print_response(&response);
+            if copy {
+                copy_response(&response);
+            }
 
-    match model.generate_response(&modified_prompt).await {
+            let blocks = extract_code_blocks(&response);
+            if blocks.is_empty() {
+                eprintln!("{}", format_error("No code blocks found in the response, nothing was written."));
+                std::process::exit(1);
+            }
+            if let Some(dir) = save_code {
+                let (written, skipped) = save_all(&blocks, &dir, overwrite)?;
+                for path in written {
+                    eprintln!("Wrote {}", path.display());
+                }
+                for path in &skipped {
+                    eprintln!("{}", format_error(&format!("{} already exists, it was not overwritten.", path.display())));
+                }
+                if !skipped.is_empty() {
+                    eprintln!("Add --overwrite to replace existing files.");
+                    std::process::exit(1);
+                }
+            }
+        }
         Ok(response) => {
             // Print the response to the console, formatted with headers, colors, and sectioning
-            print_formatted_response(&response, is_command_mode);
+            conversation.print_response(&response);
 
-            // Ask the user if they would like to continue the conversation
-            let yes_no = color_text("(y/n)", "yellow");
-            println!("\nWould you like to continue the conversation? {}", yes_no);
+            if copy {
+                copy_response(&response);
+            }
 
-            let mut input = String::new();
-            io::stdin().read_line(&mut input)?;
+            // Offer to run each command, a failure can be sent back as the next turn
+            if run_steps {
+                if let Err(e) = run_response_steps(&mut conversation, &response).await {
+                    eprintln!("{}", format_error(&e.to_string()));
+                }
+            }
 
-            if input.trim().to_lowercase() == "y" {
-                println!("\nAt anytime, enter quit to exit the conversation.");
-                continue_conversation(model, is_command_mode).await?;
-            } else if input.trim().to_lowercase() != "n" {
-                eprintln!("Invalid input. Conversation ended.");
+            // Ask the user if they would like to continue the conversation
+            if ask_yes_no("\nWould you like to continue the conversation?")? {
+                println!("\nAt anytime, enter quit to exit the conversation, or /help to see commands.");
+                continue_conversation(conversation).await?;
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", format_error(&e.to_string()));
+            if emit_script.is_some() || emit_json || save_code.is_some() {
+                std::process::exit(1);
             }
         }
-        Err(e) => eprintln!("{}", format_error(&e.to_string())),
     }
     Ok(())
 }
+
+// Removes `flag` and the value after it from the arguments
+fn take_option(args: &mut Vec<String>, flag: &str) -> Result<Option<String>, String> {
+    let Some(index) = args.iter().skip(1).position(|arg| arg == flag).map(|i| i + 1) else {
+        return Ok(None);
+    };
+    if index + 1 >= args.len() {
+        return Err(format!("{} requires a value", flag));
+    }
+
+    let value = args.remove(index + 1);
+    args.remove(index);
+    Ok(Some(value))
+}
+
+// The raw text, without the colors and code block banners of the printed version. The note goes to stderr
+// so it never mixes with --emit-json output.
+fn copy_response(response: &str) {
+    match copy_to_clipboard(response) {
+        Ok(()) => eprintln!("\nCopied the response to the clipboard."),
+        Err(e) => eprintln!("{}", format_error(&format!("Could not copy: {}", e))),
+    }
+}
+
+fn take_switch(args: &mut Vec<String>, flag: &str) -> bool {
+    let before = args.len();
+    args.retain(|arg| arg != flag);
+    args.len() != before
+}
+
+// Scripts are written executable so they can be run directly after reading them
+fn write_script(path: &str, script: &str) -> io::Result<()> {
+    fs::write(path, script)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+fn print_usage(program: &str) {
+    eprintln!("Usage: {} <-cl|-gpt> [--model <name>] [--copy] [modifiers...] \"your question in quotes\"", program);
+    eprintln!("       {} <-cl|-gpt> -c \"your question\" --emit-script <file.sh> | --emit-json | --run", program);
+    eprintln!("       {} <-cl|-gpt> \"your question\" --save-code <dir> [--overwrite]", program);
+    eprintln!("       {} commit [-cl|-gpt]", program);
+    eprintln!("       {} completions <bash|zsh|fish>", program);
+    eprintln!("       {} explain [-cl|-gpt] -- <command line>", program);
+    eprintln!("       {} fix [-cl|-gpt] [--rerun] [--command <cmd>]", program);
+    eprintln!("       {} history <list|search> ...", program);
+    eprintln!("       {} export <session-id> [--format md|html]", program);
+    eprintln!("       {} import <conversations.json>", program);
+    eprintln!("       {} resume <session-id>", program);
+    eprintln!("       {} review [-cl|-gpt] [<range>|--staged]", program);
+    eprintln!("       {} run [-cl|-gpt] -- <command>", program);
+    eprintln!("       {} shell-init <bash|zsh|fish> [-cl|-gpt]", program);
+    eprintln!("       {} suggest [-cl|-gpt] -- <what you want to do>", program);
+
+    // Built-in modifiers come first, then any declared in the config file
+    eprintln!("\nModifiers:");
+    for modifier in all_modifiers() {
+        eprintln!("  {:<6} {:<10} {}", modifier.flag(), modifier.name(), modifier.description());
+    }
+    eprintln!("\nCustom modifiers can be added in {}", config::config_path().display());
+}
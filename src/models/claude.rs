@@ -2,18 +2,46 @@
 
 // claude doesn't have a reliable free-tier API, so this code is for demonstration purposes only.
 
-use crate::models::AIModel;
+use crate::models::{AIModel, Transcript, DEFAULT_MAX_TOKENS};
 use async_trait::async_trait;
 use dotenv::dotenv;
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 use serde_json::json;
 use std::env;
 
-pub struct Claude;
+const MODEL: &str = "claude-3-sonnet-20240229";
+
+pub struct Claude {
+    model: String,
+}
+
+impl Claude {
+    pub fn new() -> Self {
+        Claude {
+            model: MODEL.to_string(),
+        }
+    }
+}
 
 #[async_trait]
 impl AIModel for Claude {
-    async fn generate_response(&mut self, prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
+    fn provider(&self) -> &'static str {
+        "claude"
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn set_model(&mut self, name: &str) {
+        self.model = name.to_string();
+    }
+
+    fn known_models(&self) -> &'static [&'static str] {
+        &["claude-3-haiku-20240307", "claude-3-sonnet-20240229", "claude-3-opus-20240229", "claude-3-5-sonnet-20240620"]
+    }
+
+    async fn generate_response(&self, transcript: &Transcript) -> Result<String, Box<dyn std::error::Error>> {
         dotenv().ok();
         
         let api_key = env::var("ANTHROPIC_API_KEY")
@@ -29,17 +57,32 @@ impl AIModel for Claude {
         
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
-        let body = json!({
-            "model": "claude-3-sonnet-20240229",
-            "max_tokens": 1000,
-            "messages": [
-                {
-                    "role": "user",
-                    "content": prompt
-                }
-            ]
+        let messages: Vec<serde_json::Value> = transcript
+            .messages
+            .iter()
+            .map(|message| {
+                json!({
+                    "role": message.role_name(),
+                    "content": message.content
+                })
+            })
+            .collect();
+
+        let mut body = json!({
+            "model": self.model,
+            "max_tokens": transcript.params.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            "messages": messages
         });
 
+        // Anthropic takes the system prompt as a top-level field rather than a message
+        if let Some(system_prompt) = &transcript.system {
+            body["system"] = json!(system_prompt);
+        }
+
+        if let Some(temperature) = transcript.params.temperature {
+            body["temperature"] = json!(temperature);
+        }
+
         let response = client
             .post("https://api.anthropic.com/v1/messages")
             .headers(headers)
@@ -54,7 +97,7 @@ impl AIModel for Claude {
         if let Some(content) = response_body["content"][0]["text"].as_str() {
             Ok(content.to_string())
         } else {
-            println!("Response structure: {:?}", response_text);
+            eprintln!("Response structure: {:?}", response_text);
             Err("Failed to parse Claude's response".into())
         }
     }
@@ -2,34 +2,50 @@
 
 // this is a module that contains the implementation of the GPT model.
 
-use crate::models::AIModel;
-use crate::syntax_highlighter::highlight_code_blocks;
+use crate::models::{AIModel, Transcript, DEFAULT_MAX_TOKENS};
 use async_trait::async_trait;
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 use serde_json::json;
 use std::env;
 
+const MODEL: &str = "gpt-3.5-turbo";
+const DEFAULT_SYSTEM_PROMPT: &str = "You are a helpful assistant.";
+
+#[allow(clippy::upper_case_acronyms)]
 pub struct GPT {
-    conversation_history: Vec<serde_json::Value>,
+    model: String,
 }
 
 // Implement (Rust trait that defines behavior) the AIModel trait for GPT
 impl GPT {
     pub fn new() -> Self {
         GPT {
-            conversation_history: vec![json!({
-                "role": "system",
-                "content": "You are a helpful assistant."
-            })],
+            model: MODEL.to_string(),
         }
     }
 }
 
 #[async_trait]
 impl AIModel for GPT {
+    fn provider(&self) -> &'static str {
+        "gpt"
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn set_model(&mut self, name: &str) {
+        self.model = name.to_string();
+    }
+
+    fn known_models(&self) -> &'static [&'static str] {
+        &["gpt-3.5-turbo", "gpt-4", "gpt-4-turbo", "gpt-4o", "gpt-4o-mini"]
+    }
+
     async fn generate_response(
-        &mut self,
-        prompt: &str,
+        &self,
+        transcript: &Transcript,
     ) -> Result<String, Box<dyn std::error::Error>> {
         dotenv::dotenv().ok();
 
@@ -48,17 +64,29 @@ impl AIModel for GPT {
 
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
-        self.conversation_history.push(json!({
-            "role": "user",
-            "content": prompt
+        // OpenAI expects the system prompt as the first message of the list
+        let system_prompt = transcript.system.as_deref().unwrap_or(DEFAULT_SYSTEM_PROMPT);
+        let mut messages = vec![json!({
+            "role": "system",
+            "content": system_prompt
+        })];
+        messages.extend(transcript.messages.iter().map(|message| {
+            json!({
+                "role": message.role_name(),
+                "content": message.content
+            })
         }));
 
-        let body = json!({
-            "model": "gpt-3.5-turbo",
-            "messages": self.conversation_history,
-            "max_tokens": 1000
+        let mut body = json!({
+            "model": self.model,
+            "messages": messages,
+            "max_tokens": transcript.params.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)
         });
 
+        if let Some(temperature) = transcript.params.temperature {
+            body["temperature"] = json!(temperature);
+        }
+
         // Send a POST request to the OpenAI API
         let response = client
             .post("https://api.openai.com/v1/chat/completions")
@@ -72,17 +100,10 @@ impl AIModel for GPT {
         let response_body: serde_json::Value = serde_json::from_str(&response_text)?;
 
         if let Some(content) = response_body["choices"][0]["message"]["content"].as_str() {
-            self.conversation_history.push(json!({
-                "role": "assistant",
-                "content": content
-            }));
-
-            // Check for code blocks, apply syntax highlighting, and keyword highlighting for supplemental explanations
-            let highlighted_content = highlight_code_blocks(content);
-
-            Ok(highlighted_content)
+            // Return the raw text, highlighting happens when the response is printed
+            Ok(content.to_string())
         } else {
-            println!("Response structure: {:?}", response_text);
+            eprintln!("Response structure: {:?}", response_text);
             Err("Failed to parse GPT's response".into())
         }
     }
@@ -3,12 +3,71 @@
 pub mod claude;
 pub mod gpt;
 
+use crate::session::Role;
 use async_trait::async_trait;
 
+// The conversation in a provider-neutral shape. The conversation loop owns it and sends all of it on
+// every request, each model translates it into its own wire format. This is what lets the provider
+// change between turns without losing history.
+pub struct Transcript {
+    pub system: Option<String>,
+    pub messages: Vec<Message>,
+    pub params: GenerationParams,
+}
+
+// Sampling settings for a request. Unset values use the provider's defaults.
+#[derive(Clone, Copy, Default)]
+pub struct GenerationParams {
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+}
+
+// Used when no modifier sets a limit
+pub const DEFAULT_MAX_TOKENS: u32 = 1000;
+
+pub struct Message {
+    pub role: Role,
+    pub content: String,
+}
+
+impl Message {
+    // Both OpenAI and Anthropic name the roles "user" and "assistant"
+    pub fn role_name(&self) -> &'static str {
+        match self.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        }
+    }
+}
+
 #[async_trait]
 pub trait AIModel {
+    // Short provider name used in saved sessions and filters, e.g. "gpt" or "claude"
+    fn provider(&self) -> &'static str;
+
+    // The model identifier sent to the provider's API
+    fn model_name(&self) -> &str;
+
+    fn set_model(&mut self, name: &str);
+
+    // Model names offered by shell completion. Any other name the provider accepts works too.
+    fn known_models(&self) -> &'static [&'static str];
+
+    // Sends the whole transcript and returns the text of the reply to its last message
     async fn generate_response(
-        &mut self,
-        prompt: &str,
+        &self,
+        transcript: &Transcript,
     ) -> Result<String, Box<dyn std::error::Error>>;
 }
+
+// Every provider name from_provider understands
+pub const PROVIDERS: &[&str] = &["claude", "gpt"];
+
+// Builds a model from the provider name stored in a session
+pub fn from_provider(provider: &str) -> Option<Box<dyn AIModel>> {
+    match provider {
+        "claude" => Some(Box::new(claude::Claude::new())),
+        "gpt" => Some(Box::new(gpt::GPT::new())),
+        _ => None,
+    }
+}
@@ -0,0 +1,174 @@
+// src/process.rs
+
+// Runs a command with its output going to the terminal as it arrives, while keeping the end of it
+// so it can be shown to the model afterwards.
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+pub struct Captured {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+    // Set when the command was killed for running longer than its timeout
+    pub timed_out: bool,
+}
+
+// Keeps the last `tail_lines` lines of stderr, and of stdout when `capture_stdout` is set. Otherwise stdout
+// is left connected to the terminal, so programs that check for one keep their colors and progress bars.
+pub fn run_teed(
+    command: &mut Command,
+    capture_stdout: bool,
+    tail_lines: usize,
+) -> io::Result<Captured> {
+    let stdout = if capture_stdout {
+        Stdio::piped()
+    } else {
+        Stdio::inherit()
+    };
+    let mut child = command
+        .stdin(Stdio::inherit())
+        .stdout(stdout)
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout_reader = child
+        .stdout
+        .take()
+        .map(|pipe| tee(pipe, io::stdout, tail_lines));
+    let stderr_reader = child
+        .stderr
+        .take()
+        .map(|pipe| tee(pipe, io::stderr, tail_lines));
+
+    let status = child.wait()?;
+    let collect = |reader: Option<JoinHandle<String>>| {
+        reader
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default()
+    };
+
+    Ok(Captured {
+        status,
+        stdout: collect(stdout_reader),
+        stderr: collect(stderr_reader),
+        timed_out: false,
+    })
+}
+
+// Like run_teed with both streams captured, for code that shouldn't interact with the terminal: stdin is
+// closed and the command is killed, with everything it started, once it runs longer than `timeout`. The
+// output is only copied to the terminal when `echo` is set.
+pub fn run_with_timeout(
+    command: &mut Command,
+    echo: bool,
+    tail_lines: usize,
+    timeout: Duration,
+) -> io::Result<Captured> {
+    // Its own process group, so the children of a script are killed with it
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout_reader = child.stdout.take().map(|pipe| {
+        if echo {
+            tee(pipe, io::stdout, tail_lines)
+        } else {
+            tee(pipe, io::sink, tail_lines)
+        }
+    });
+    let stderr_reader = child.stderr.take().map(|pipe| {
+        if echo {
+            tee(pipe, io::stderr, tail_lines)
+        } else {
+            tee(pipe, io::sink, tail_lines)
+        }
+    });
+
+    let started = Instant::now();
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            timed_out = true;
+            kill_group(&mut child);
+            break child.wait()?;
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+
+    let collect = |reader: Option<JoinHandle<String>>| {
+        reader
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default()
+    };
+
+    Ok(Captured {
+        status,
+        stdout: collect(stdout_reader),
+        stderr: collect(stderr_reader),
+        timed_out,
+    })
+}
+
+fn kill_group(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        let _ = Command::new("kill")
+            .args(["-KILL", "--", &format!("-{}", child.id())])
+            .stderr(Stdio::null())
+            .status();
+    }
+    let _ = child.kill();
+}
+
+// Copies output to the terminal as soon as it's read, so partial lines like progress bars show up too,
+// and keeps the last lines
+fn tee<R, W>(mut pipe: R, output: fn() -> W, tail_lines: usize) -> JoinHandle<String>
+where
+    R: Read + Send + 'static,
+    W: Write + 'static,
+{
+    thread::spawn(move || {
+        let mut tail: VecDeque<String> = VecDeque::new();
+        let mut current = Vec::new();
+        let mut buffer = [0; 4096];
+
+        while let Ok(read) = pipe.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            let mut out = output();
+            let _ = out.write_all(&buffer[..read]);
+            let _ = out.flush();
+
+            for &byte in &buffer[..read] {
+                current.push(byte);
+                if byte == b'\n' {
+                    if tail.len() == tail_lines {
+                        tail.pop_front();
+                    }
+                    tail.push_back(String::from_utf8_lossy(&current).into_owned());
+                    current.clear();
+                }
+            }
+        }
+
+        let mut text: String = tail.into_iter().collect();
+        text.push_str(&String::from_utf8_lossy(&current));
+        text
+    })
+}
@@ -0,0 +1,26 @@
+// src/prompt.rs
+
+// Questions asked on the terminal before doing something, e.g. running a command or writing a file.
+
+use crate::text_formatter::color_text;
+use std::io::{self, Write};
+
+// True when the answer is y, anything else (including end of input) is a no
+pub fn ask_yes_no(question: &str) -> io::Result<bool> {
+    print!("{} {} ", question, color_text("(y/n)", "yellow"));
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().eq_ignore_ascii_case("y"))
+}
+
+// For things that are hard to take back, a stray y isn't enough
+pub fn ask_typed_yes(question: &str) -> io::Result<bool> {
+    print!("{} ", question);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().eq_ignore_ascii_case("yes"))
+}
@@ -1,14 +1,124 @@
 // src/response_types/command.rs
 
-use super::ResponseModifier;
+use super::{Renderer, ResponseModifier};
+use crate::environment;
+use crate::models::GenerationParams;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+const SYSTEM_PROMPT: &str = "You are a command line assistant. Answer with a numbered list of steps, one command per step, \
+                             with each command in backticks or a fenced code block exactly as it should be typed. \
+                             Do not add introductions or closing remarks.";
+
+lazy_static! {
+    static ref LIST_ITEM: Regex = Regex::new(r"^\s*\d+[.)]\s").unwrap();
+    // The instructions followed by what's known about the user's machine, see environment.rs
+    static ref SYSTEM_PROMPT_WITH_ENVIRONMENT: String = match environment::context() {
+        Some(context) => format!("{}\n\n{}", SYSTEM_PROMPT, context),
+        None => SYSTEM_PROMPT.to_string(),
+    };
+}
 
 pub struct Command;
 
 impl ResponseModifier for Command {
-    fn modify_prompt(&self, prompt: &str) -> String {
-        format!(
-            "{} Please respond with just the commands to execute the task in an ordered list.",
-            prompt
-        )
+    fn name(&self) -> &str {
+        "command"
+    }
+
+    fn flag(&self) -> &str {
+        "-c"
+    }
+
+    fn description(&self) -> &str {
+        "Respond with an ordered list of commands"
+    }
+
+    fn suffix(&self) -> Option<&str> {
+        Some("Please respond with just the commands to execute the task in an ordered list.")
+    }
+
+    fn system_prompt(&self) -> Option<&str> {
+        Some(&SYSTEM_PROMPT_WITH_ENVIRONMENT)
+    }
+
+    // Commands should come out the same way every time, not creatively
+    fn adjust_params(&self, params: &mut GenerationParams) {
+        params.temperature = Some(0.2);
+    }
+
+    fn post_process(&self, response: &str) -> String {
+        strip_prose(response)
+    }
+
+    fn renderer(&self) -> Renderer {
+        Renderer::Command
+    }
+}
+
+// Drops the lines before the first step and after the last one, like "Here are the steps:" or "Let me know if...".
+// A step is a numbered list item, or a fenced code block, and the indented lines that belong to it.
+// Responses without any numbered list are left alone.
+pub fn strip_prose(response: &str) -> String {
+    let lines: Vec<&str> = response.lines().collect();
+
+    let mut in_fence = false;
+    let mut has_list = false;
+    let mut steps = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let is_fence = line.trim_start().starts_with("```");
+        let is_item = !in_fence && LIST_ITEM.is_match(line);
+        let is_indented = line.starts_with([' ', '\t']) && !line.trim().is_empty();
+
+        if is_fence || in_fence || is_item || is_indented {
+            steps.push(i);
+        }
+        has_list |= is_item;
+        if is_fence {
+            in_fence = !in_fence;
+        }
+    }
+
+    match (steps.first(), steps.last()) {
+        (Some(&first), Some(&last)) if has_list => lines[first..=last].join("\n"),
+        _ => response.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_text_around_the_steps() {
+        let response = "Sure! Here are the steps:\n\n\
+                        1. Initialize the repository: `git init`\n   \
+                        This creates a .git directory.\n\
+                        2) Add a remote:\n\
+                        ```bash\n\
+                        git remote add origin URL\n\
+                        ```\n\n\
+                        Let me know if you need anything else!";
+        assert_eq!(
+            strip_prose(response),
+            "1. Initialize the repository: `git init`\n   \
+             This creates a .git directory.\n\
+             2) Add a remote:\n\
+             ```bash\n\
+             git remote add origin URL\n\
+             ```"
+        );
+    }
+
+    #[test]
+    fn keeps_numbered_lines_inside_code_blocks() {
+        let response = "Run this:\n```\n1. not a step\n```\n1. Done";
+        assert_eq!(strip_prose(response), "```\n1. not a step\n```\n1. Done");
+    }
+
+    #[test]
+    fn leaves_responses_without_a_list_alone() {
+        let response = "Use `ls -la`, it lists hidden files too.";
+        assert_eq!(strip_prose(response), response);
     }
 }
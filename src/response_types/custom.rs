@@ -0,0 +1,85 @@
+// src/response_types/custom.rs
+
+// A response modifier declared in the config file instead of in code.
+
+use super::command::strip_prose;
+use super::{Renderer, ResponseModifier};
+use crate::config::ModifierConfig;
+use crate::models::GenerationParams;
+
+pub struct Custom {
+    config: &'static ModifierConfig,
+    prefix: &'static str,
+    suffix: &'static str,
+}
+
+impl Custom {
+    // Templates place the prompt with {prompt}, the text around it becomes the prefix and suffix.
+    // Without the placeholder the whole template goes after the prompt, like the built-ins do.
+    pub fn new(config: &'static ModifierConfig) -> Self {
+        let (prefix, suffix) = config
+            .template
+            .split_once("{prompt}")
+            .unwrap_or(("", &config.template));
+
+        Custom {
+            config,
+            prefix,
+            suffix,
+        }
+    }
+}
+
+impl ResponseModifier for Custom {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn flag(&self) -> &str {
+        &self.config.flag
+    }
+
+    fn description(&self) -> &str {
+        &self.config.description
+    }
+
+    fn prefix(&self) -> Option<&str> {
+        Some(self.prefix)
+    }
+
+    fn suffix(&self) -> Option<&str> {
+        Some(self.suffix)
+    }
+
+    fn system_prompt(&self) -> Option<&str> {
+        self.config.system.as_deref()
+    }
+
+    fn adjust_params(&self, params: &mut GenerationParams) {
+        if let Some(temperature) = self.config.temperature {
+            params.temperature = Some(temperature);
+        }
+        if let Some(max_tokens) = self.config.max_tokens {
+            params.max_tokens = Some(max_tokens);
+        }
+    }
+
+    fn post_process(&self, response: &str) -> String {
+        if self.config.strip_prose {
+            strip_prose(response)
+        } else {
+            response.to_string()
+        }
+    }
+
+    fn renderer(&self) -> Renderer {
+        match self.config.renderer.as_deref() {
+            Some("command") => Renderer::Command,
+            _ => Renderer::Plain,
+        }
+    }
+
+    fn conflicts(&self) -> &[String] {
+        &self.config.conflicts
+    }
+}
@@ -0,0 +1,44 @@
+// src/response_types/diff.rs
+
+use super::ResponseModifier;
+use crate::models::GenerationParams;
+use lazy_static::lazy_static;
+
+const SYSTEM_PROMPT: &str = "When you change a file, reply with a unified diff in a ```diff block. \
+                             Start each file with --- a/<path> and +++ b/<path> lines using the path exactly as it was given, \
+                             begin each hunk with an @@ -start,count +start,count @@ line, \
+                             and keep three lines of unchanged context around every change.";
+
+lazy_static! {
+    static ref CONFLICTS: Vec<String> = vec!["replace".to_string()];
+}
+
+// Asks for file changes as a unified diff, which /apply can write to attached files
+pub struct Diff;
+
+impl ResponseModifier for Diff {
+    fn name(&self) -> &str {
+        "diff"
+    }
+
+    fn flag(&self) -> &str {
+        "-d"
+    }
+
+    fn description(&self) -> &str {
+        "Answer with file changes as a unified diff"
+    }
+
+    fn system_prompt(&self) -> Option<&str> {
+        Some(SYSTEM_PROMPT)
+    }
+
+    // Context lines have to match the file, so no creative rewording
+    fn adjust_params(&self, params: &mut GenerationParams) {
+        params.temperature = Some(0.2);
+    }
+
+    fn conflicts(&self) -> &[String] {
+        &CONFLICTS
+    }
+}
@@ -1,8 +1,198 @@
 // src/response_types/mod.rs
 
 pub mod command;
+pub mod custom;
+pub mod diff;
+pub mod replace;
 pub mod short;
+pub mod stack;
 
+pub use stack::ModifierStack;
+
+use crate::config::config;
+use crate::models::GenerationParams;
+
+// How a response is printed. Command responses get their ordered list numbers colored.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Renderer {
+    Plain,
+    Command,
+}
+
+// A response modifier contributes instructions around the user's prompt and to the system prompt.
+// Modifiers can be stacked, see ModifierStack for how their parts are combined.
 pub trait ResponseModifier {
-    fn modify_prompt(&self, prompt: &str) -> String;
+    // The mode name stored in sessions and used by /mode, e.g. "short"
+    fn name(&self) -> &str;
+
+    // The command line flag, e.g. "-s"
+    fn flag(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    // Instructions placed before the prompt
+    fn prefix(&self) -> Option<&str> {
+        None
+    }
+
+    // Instructions placed after the prompt
+    fn suffix(&self) -> Option<&str> {
+        None
+    }
+
+    // Extra system instructions sent alongside the prompt. Models follow these more closely than text in the prompt.
+    fn system_prompt(&self) -> Option<&str> {
+        None
+    }
+
+    // Changes sampling settings for the request, e.g. a lower temperature for more predictable commands
+    fn adjust_params(&self, _params: &mut GenerationParams) {}
+
+    // Cleans up the reply before it is printed or used. Sessions keep the reply as it came.
+    fn post_process(&self, response: &str) -> String {
+        response.to_string()
+    }
+
+    fn renderer(&self) -> Renderer {
+        Renderer::Plain
+    }
+
+    // Names of modifiers this one can't be combined with. When both are given, the later one wins.
+    fn conflicts(&self) -> &[String] {
+        &[]
+    }
+}
+
+// The built-in modifiers followed by the ones declared in the config file.
+// A custom modifier with a built-in's name or flag replaces it.
+pub fn all_modifiers() -> Vec<Box<dyn ResponseModifier>> {
+    let custom: Vec<Box<dyn ResponseModifier>> = config()
+        .modifiers
+        .iter()
+        .map(|modifier| Box::new(custom::Custom::new(modifier)) as Box<dyn ResponseModifier>)
+        .collect();
+
+    let built_in: Vec<Box<dyn ResponseModifier>> = vec![
+        Box::new(short::Short),
+        Box::new(command::Command),
+        Box::new(diff::Diff),
+        Box::new(replace::Replace),
+    ];
+
+    let mut modifiers: Vec<Box<dyn ResponseModifier>> = built_in
+        .into_iter()
+        .filter(|b| {
+            !custom
+                .iter()
+                .any(|c| c.name() == b.name() || c.flag() == b.flag())
+        })
+        .collect();
+    modifiers.extend(custom);
+    modifiers
+}
+
+// Looks up a single modifier by the name stored in sessions
+pub fn from_name(name: &str) -> Option<Box<dyn ResponseModifier>> {
+    all_modifiers()
+        .into_iter()
+        .find(|modifier| modifier.name() == name)
+}
+
+pub fn from_flag(flag: &str) -> Option<Box<dyn ResponseModifier>> {
+    all_modifiers()
+        .into_iter()
+        .find(|modifier| modifier.flag() == flag)
+}
+
+// A saved reply the way it's shown for the mode it was written in
+pub fn post_process_for(mode: Option<&str>, response: &str) -> String {
+    mode.map_or_else(
+        || response.to_string(),
+        |mode| ModifierStack::from_mode(mode).post_process(response),
+    )
+}
+
+pub fn renderer_for(mode: Option<&str>) -> Renderer {
+    mode.map_or(Renderer::Plain, |mode| {
+        ModifierStack::from_mode(mode).renderer()
+    })
+}
+
+// Words that make a leading flag the subject of the prompt, as in "-r flag of cp?", rather than a mode switch
+const FLAG_NOUNS: &[&str] = &[
+    "flag",
+    "flags",
+    "option",
+    "options",
+    "switch",
+    "switches",
+    "argument",
+    "arguments",
+];
+
+// A prompt starting with one or more modifier flags such as -s -c, or -n for no modifier, is sent in that mode.
+// Returns the mode and the rest of the prompt, or None without a prefix or when the flags are asked about.
+pub fn split_mode_prefix(prompt: &str) -> Option<(Option<String>, &str)> {
+    let mut stack = ModifierStack::default();
+    let mut rest = prompt;
+    let mut found = false;
+
+    while let Some((flag, remainder)) = rest.split_once(char::is_whitespace) {
+        if flag == "-n" {
+            stack = ModifierStack::default();
+        } else if let Some(modifier) = from_flag(flag) {
+            stack.push(modifier);
+        } else {
+            break;
+        }
+        found = true;
+        rest = remainder.trim_start();
+    }
+
+    let next_word = rest
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+    if FLAG_NOUNS.contains(&next_word.as_str()) {
+        return None;
+    }
+
+    found.then(|| (stack.mode(), rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leading_flags_pick_the_mode() {
+        assert_eq!(
+            split_mode_prefix("-s -c list open ports"),
+            Some((Some("short+command".to_string()), "list open ports"))
+        );
+        assert_eq!(
+            split_mode_prefix("-s  -n   list open ports"),
+            Some((None, "list open ports"))
+        );
+    }
+
+    #[test]
+    fn prompts_without_a_known_flag_are_left_alone() {
+        assert_eq!(split_mode_prefix("list open ports"), None);
+        assert_eq!(split_mode_prefix("-x list open ports"), None);
+        assert_eq!(split_mode_prefix("-s"), None);
+        assert_eq!(split_mode_prefix("-r, -f in rm?"), None);
+    }
+
+    #[test]
+    fn questions_about_a_flag_are_not_mode_switches() {
+        assert_eq!(split_mode_prefix("-r flag of cp?"), None);
+        assert_eq!(split_mode_prefix("-d option in cut"), None);
+        assert_eq!(split_mode_prefix("-s -c Flags, what do they do"), None);
+        assert_eq!(
+            split_mode_prefix("-s flagging a process in ps"),
+            Some((Some("short".to_string()), "flagging a process in ps"))
+        );
+    }
 }
@@ -0,0 +1,47 @@
+// src/response_types/replace.rs
+
+use super::ResponseModifier;
+use crate::models::GenerationParams;
+use lazy_static::lazy_static;
+
+const SYSTEM_PROMPT: &str = "When you change a file, write the file's path on its own line, exactly as it was given, followed by one block per change:\n\
+                             <<<<<<< SEARCH\n\
+                             the lines to replace, copied exactly from the file\n\
+                             =======\n\
+                             the lines to put in their place\n\
+                             >>>>>>> REPLACE\n\
+                             The SEARCH lines must match the file including indentation, and be long enough to appear only once in it.";
+
+lazy_static! {
+    static ref CONFLICTS: Vec<String> = vec!["diff".to_string()];
+}
+
+// Asks for file changes as search/replace blocks, which /apply can write to attached files.
+// Models get these right more often than diff line numbers.
+pub struct Replace;
+
+impl ResponseModifier for Replace {
+    fn name(&self) -> &str {
+        "replace"
+    }
+
+    fn flag(&self) -> &str {
+        "-r"
+    }
+
+    fn description(&self) -> &str {
+        "Answer with file changes as search/replace blocks"
+    }
+
+    fn system_prompt(&self) -> Option<&str> {
+        Some(SYSTEM_PROMPT)
+    }
+
+    fn adjust_params(&self, params: &mut GenerationParams) {
+        params.temperature = Some(0.2);
+    }
+
+    fn conflicts(&self) -> &[String] {
+        &CONFLICTS
+    }
+}
@@ -5,10 +5,19 @@ use super::ResponseModifier;
 pub struct Short;
 
 impl ResponseModifier for Short {
-    fn modify_prompt(&self, prompt: &str) -> String {
-        format!(
-            "{} Please be sure to keep your response to a paragraph or less.",
-            prompt
-        )
+    fn name(&self) -> &str {
+        "short"
+    }
+
+    fn flag(&self) -> &str {
+        "-s"
+    }
+
+    fn description(&self) -> &str {
+        "Keep the response to a paragraph or less"
+    }
+
+    fn suffix(&self) -> Option<&str> {
+        Some("Please be sure to keep your response to a paragraph or less.")
     }
 }
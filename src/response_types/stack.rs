@@ -0,0 +1,231 @@
+// src/response_types/stack.rs
+
+// Several response modifiers applied together, e.g. `-s -c` for a short list of commands.
+//
+// Each modifier's prefix, suffix and system instructions are kept in the order the modifiers were given,
+// with repeated text included once. A modifier that lists another in its conflicts replaces it, so the
+// combined instructions don't ask for two contradicting things. The stack is stored in sessions as the
+// modifier names joined with "+", e.g. "short+command".
+
+use super::{from_name, Renderer, ResponseModifier};
+use crate::models::GenerationParams;
+
+#[derive(Default)]
+pub struct ModifierStack {
+    modifiers: Vec<Box<dyn ResponseModifier>>,
+}
+
+impl ModifierStack {
+    // Unknown names, e.g. from a modifier that was removed from the config since, are skipped
+    pub fn from_mode(mode: &str) -> Self {
+        let mut stack = ModifierStack::default();
+        for name in mode.split('+').map(str::trim) {
+            if let Some(modifier) = from_name(name) {
+                stack.push(modifier);
+            }
+        }
+        stack
+    }
+
+    // Adds a modifier on top, dropping any earlier one it conflicts with. Adding one twice has no effect.
+    pub fn push(&mut self, modifier: Box<dyn ResponseModifier>) {
+        if self.modifiers.iter().any(|m| m.name() == modifier.name()) {
+            return;
+        }
+
+        self.modifiers
+            .retain(|existing| !conflicting(existing.as_ref(), modifier.as_ref()));
+        self.modifiers.push(modifier);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modifiers.is_empty()
+    }
+
+    // The name stored in sessions, None when no modifier is applied
+    pub fn mode(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let names: Vec<&str> = self.modifiers.iter().map(|m| m.name()).collect();
+        Some(names.join("+"))
+    }
+
+    // The prompt with every prefix before it and every suffix after it
+    pub fn apply(&self, prompt: &str) -> String {
+        let prefixes = unique(self.modifiers.iter().filter_map(|m| m.prefix()));
+        let suffixes = unique(self.modifiers.iter().filter_map(|m| m.suffix()));
+
+        let mut parts = prefixes;
+        parts.push(prompt);
+        parts.extend(suffixes);
+        parts.join(" ")
+    }
+
+    pub fn system_prompt(&self) -> Option<String> {
+        let parts = unique(self.modifiers.iter().filter_map(|m| m.system_prompt()));
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join("\n\n"))
+        }
+    }
+
+    // Each modifier adjusts the settings in turn, so a later one overrides an earlier one
+    pub fn params(&self) -> GenerationParams {
+        let mut params = GenerationParams::default();
+        for modifier in &self.modifiers {
+            modifier.adjust_params(&mut params);
+        }
+        params
+    }
+
+    pub fn post_process(&self, response: &str) -> String {
+        self.modifiers
+            .iter()
+            .fold(response.to_string(), |text, modifier| {
+                modifier.post_process(&text)
+            })
+    }
+
+    // The last modifier with its own way of rendering decides
+    pub fn renderer(&self) -> Renderer {
+        self.modifiers
+            .iter()
+            .map(|m| m.renderer())
+            .rfind(|renderer| *renderer != Renderer::Plain)
+            .unwrap_or(Renderer::Plain)
+    }
+}
+
+fn conflicting(a: &dyn ResponseModifier, b: &dyn ResponseModifier) -> bool {
+    a.conflicts().iter().any(|name| name == b.name())
+        || b.conflicts().iter().any(|name| name == a.name())
+}
+
+// Drops empty and repeated instructions, keeping the first occurrence
+fn unique<'a>(parts: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut seen: Vec<&str> = Vec::new();
+    for part in parts.map(str::trim).filter(|part| !part.is_empty()) {
+        if !seen.contains(&part) {
+            seen.push(part);
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModifierConfig;
+    use crate::response_types::{
+        command::Command, custom::Custom, diff::Diff, replace::Replace, short::Short,
+    };
+
+    const SHORT: &str = "Please be sure to keep your response to a paragraph or less.";
+    const COMMAND: &str =
+        "Please respond with just the commands to execute the task in an ordered list.";
+
+    fn stack(modifiers: Vec<Box<dyn ResponseModifier>>) -> ModifierStack {
+        let mut stack = ModifierStack::default();
+        for modifier in modifiers {
+            stack.push(modifier);
+        }
+        stack
+    }
+
+    fn custom(json: &str) -> Box<dyn ResponseModifier> {
+        let config: ModifierConfig = serde_json::from_str(json).unwrap();
+        Box::new(Custom::new(Box::leak(Box::new(config))))
+    }
+
+    #[test]
+    fn suffixes_follow_the_order_given() {
+        let short_command = stack(vec![Box::new(Short), Box::new(Command)]);
+        assert_eq!(short_command.mode().as_deref(), Some("short+command"));
+        assert_eq!(
+            short_command.apply("list files"),
+            format!("list files {} {}", SHORT, COMMAND)
+        );
+        assert!(short_command.renderer() == Renderer::Command);
+        assert_eq!(short_command.params().temperature, Some(0.2));
+        assert!(short_command
+            .system_prompt()
+            .unwrap()
+            .starts_with("You are a command line assistant."));
+
+        let command_short = stack(vec![Box::new(Command), Box::new(Short)]);
+        assert_eq!(command_short.mode().as_deref(), Some("command+short"));
+        assert_eq!(
+            command_short.apply("list files"),
+            format!("list files {} {}", COMMAND, SHORT)
+        );
+        // Short renders plainly, so command's renderer still applies
+        assert!(command_short.renderer() == Renderer::Command);
+    }
+
+    #[test]
+    fn prefixes_go_before_the_prompt() {
+        let stack = stack(vec![
+            Box::new(Short),
+            custom(r#"{"name": "rust", "flag": "-r", "template": "In Rust: {prompt}"}"#),
+        ]);
+        assert_eq!(
+            stack.apply("read a file"),
+            format!("In Rust: read a file {}", SHORT)
+        );
+    }
+
+    #[test]
+    fn the_same_modifier_twice_counts_once() {
+        let stack = stack(vec![Box::new(Short), Box::new(Command), Box::new(Short)]);
+        assert_eq!(stack.mode().as_deref(), Some("short+command"));
+        assert_eq!(stack.apply("x"), format!("x {} {}", SHORT, COMMAND));
+    }
+
+    #[test]
+    fn repeated_instructions_are_included_once() {
+        let brief = custom(&format!(
+            r#"{{"name": "brief", "flag": "-b", "template": "{}", "system": "Be terse."}}"#,
+            SHORT
+        ));
+        let terse =
+            custom(r#"{"name": "terse", "flag": "-t", "template": "", "system": "Be terse."}"#);
+        let stack = stack(vec![Box::new(Short), brief, terse]);
+
+        assert_eq!(stack.mode().as_deref(), Some("short+brief+terse"));
+        assert_eq!(stack.apply("x"), format!("x {}", SHORT));
+        assert_eq!(stack.system_prompt().as_deref(), Some("Be terse."));
+    }
+
+    #[test]
+    fn the_later_of_two_conflicting_modifiers_wins() {
+        let diff_then_replace = stack(vec![Box::new(Short), Box::new(Diff), Box::new(Replace)]);
+        assert_eq!(diff_then_replace.mode().as_deref(), Some("short+replace"));
+
+        let replace_then_diff = stack(vec![Box::new(Replace), Box::new(Diff)]);
+        assert_eq!(replace_then_diff.mode().as_deref(), Some("diff"));
+        assert_eq!(
+            replace_then_diff.system_prompt(),
+            Diff.system_prompt().map(str::to_string)
+        );
+    }
+
+    #[test]
+    fn later_modifiers_override_generation_settings() {
+        let warm = || {
+            custom(r#"{"name": "warm", "flag": "-w", "template": "{prompt}", "temperature": 0.9}"#)
+        };
+        assert_eq!(
+            stack(vec![Box::new(Command), warm()]).params().temperature,
+            Some(0.9)
+        );
+        assert_eq!(
+            stack(vec![warm(), Box::new(Command)]).params().temperature,
+            Some(0.2)
+        );
+        assert_eq!(ModifierStack::default().params().temperature, None);
+        assert_eq!(ModifierStack::default().mode(), None);
+    }
+}
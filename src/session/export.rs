@@ -0,0 +1,186 @@
+// src/session/export.rs
+
+// Renders a saved session as Markdown or as a self-contained HTML page for sharing.
+// Only the active branch is exported. Markdown keeps each response exactly as the model wrote it, fences included.
+// HTML runs code blocks through the same token highlighter as the terminal, using CSS classes instead of ANSI codes.
+
+use super::{timestamp, Role, Session, Turn};
+use crate::syntax_highlighter::{escape_html, highlight_code_html};
+
+const STYLE: &str = r#"
+body { background: #1e1e1e; color: #d6d6d6; font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; max-width: 860px; margin: 2rem auto; padding: 0 1rem; line-height: 1.5; }
+h1 { font-size: 1.4rem; }
+.meta { color: #797979; font-size: 0.9rem; }
+.turn { border-left: 3px solid #444; margin: 1.5rem 0; padding: 0.25rem 1rem; }
+.turn.user { border-color: #e5b567; }
+.turn.assistant { border-color: #b4d273; }
+.turn header { font-weight: bold; margin-bottom: 0.5rem; }
+.turn header .meta { font-weight: normal; margin-left: 0.5rem; }
+.text { white-space: pre-wrap; }
+code { background: #333; padding: 0 0.25rem; border-radius: 3px; }
+pre { background: #2b2b2b; padding: 0.75rem 1rem; border-radius: 4px; overflow-x: auto; }
+pre code { background: none; padding: 0; }
+.lang { color: #797979; font-size: 0.8rem; }
+.tok-comment { color: #797979; }
+.tok-variable { color: #d6d6d6; }
+.tok-literal, .tok-string { color: #e5b567; }
+.tok-method-function { color: #b4d273; }
+.tok-built-in, .tok-modifier { color: #e87d3e; }
+.tok-annotation, .tok-preprocessor-directive { color: #9e86c8; }
+.tok-keyword, .tok-type { color: #b05279; }
+.tok-operator { color: #6c99bb; }
+"#;
+
+pub fn to_markdown(session: &Session) -> String {
+    let mut markdown = format!("# {}\n\n", title_or_id(session));
+    markdown.push_str(&format!("- Session: `{}`\n", session.id));
+    markdown.push_str(&format!(
+        "- Provider: {} ({})\n",
+        session.provider, session.model
+    ));
+    markdown.push_str(&format!(
+        "- Started: {} UTC\n",
+        timestamp::format_datetime(session.created_at)
+    ));
+
+    for turn in session.path() {
+        markdown.push_str(&format!("\n## {}\n\n", turn_heading(turn)));
+        markdown.push_str(turn.content.trim_end());
+        markdown.push('\n');
+    }
+
+    markdown
+}
+
+pub fn to_html(session: &Session) -> String {
+    let title = escape_html(&title_or_id(session));
+    let mut html = String::new();
+
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!(
+        "<title>{}</title>\n<style>{}</style>\n",
+        title, STYLE
+    ));
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", title));
+    html.push_str(&format!(
+        "<p class=\"meta\">Session {} &middot; {} ({}) &middot; started {} UTC</p>\n",
+        escape_html(&session.id),
+        escape_html(&session.provider),
+        escape_html(&session.model),
+        timestamp::format_datetime(session.created_at)
+    ));
+
+    for turn in session.path() {
+        let class = match turn.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+
+        html.push_str(&format!("<section class=\"turn {}\">\n", class));
+        html.push_str(&format!(
+            "<header>{}<span class=\"meta\">{}</span></header>\n",
+            turn.role.label(),
+            escape_html(&turn_meta(turn))
+        ));
+        html.push_str(&render_content_html(&turn.content));
+        html.push_str("</section>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn title_or_id(session: &Session) -> String {
+    if session.title.is_empty() {
+        session.id.clone()
+    } else {
+        session.title.clone()
+    }
+}
+
+fn turn_meta(turn: &Turn) -> String {
+    let mut parts = vec![format!(
+        "{} UTC",
+        timestamp::format_datetime(turn.timestamp)
+    )];
+    if let Some(model) = &turn.model {
+        parts.push(model.clone());
+    }
+    if let Some(mode) = &turn.mode {
+        parts.push(format!("{} mode", mode));
+    }
+    parts.join(" · ")
+}
+
+fn turn_heading(turn: &Turn) -> String {
+    format!("{} — {}", turn.role.label(), turn_meta(turn))
+}
+
+// Splits the content on ``` fences, highlighting code blocks and escaping everything else
+fn render_content_html(content: &str) -> String {
+    let mut html = String::new();
+    let mut text = String::new();
+    let mut code = String::new();
+    let mut language_name = String::new();
+    let mut in_code_block = false;
+
+    for line in content.lines() {
+        if line.starts_with("```") {
+            if in_code_block {
+                html.push_str(&code_block_html(&language_name, &code));
+                code.clear();
+            } else {
+                html.push_str(&text_html(&text));
+                text.clear();
+                language_name = line.trim_start_matches("```").trim().to_string();
+            }
+            in_code_block = !in_code_block;
+        } else if in_code_block {
+            code.push_str(line);
+            code.push('\n');
+        } else {
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+
+    if in_code_block {
+        html.push_str(&code_block_html(&language_name, &code));
+    }
+    html.push_str(&text_html(&text));
+    html
+}
+
+fn code_block_html(language_name: &str, code: &str) -> String {
+    let label = if language_name.is_empty() {
+        String::new()
+    } else {
+        format!("<div class=\"lang\">{}</div>", escape_html(language_name))
+    };
+
+    format!(
+        "<pre>{}<code>{}</code></pre>\n",
+        label,
+        highlight_code_html(language_name, code)
+    )
+}
+
+// Escapes plain text and turns `inline code` into <code> elements
+fn text_html(text: &str) -> String {
+    let trimmed = text.trim_matches('\n');
+    if trimmed.is_empty() {
+        return String::new();
+    }
+
+    let mut html = String::from("<div class=\"text\">");
+    for (index, part) in trimmed.split('`').enumerate() {
+        if index % 2 == 1 {
+            html.push_str(&format!("<code>{}</code>", escape_html(part)));
+        } else {
+            html.push_str(&escape_html(part));
+        }
+    }
+    html.push_str("</div>\n");
+    html
+}
@@ -0,0 +1,426 @@
+// src/session/import.rs
+
+// Converts the `conversations.json` file from the ChatGPT and Claude web data exports into sessions.
+// Both exports can contain edited prompts and regenerated replies, those branches are kept as sibling turns.
+
+use super::{timestamp, title_from_prompt, Role, Session, Turn};
+use crate::models::from_provider;
+use serde_json::Value;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    ChatGpt,
+    Claude,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "chatgpt" | "gpt" | "openai" => Some(ExportFormat::ChatGpt),
+            "claude" | "anthropic" => Some(ExportFormat::Claude),
+            _ => None,
+        }
+    }
+
+    // ChatGPT conversations carry a `mapping` of message nodes, Claude conversations a `chat_messages` list
+    pub fn detect(conversations: &[Value]) -> Option<Self> {
+        let first = conversations.first()?;
+        if first.get("mapping").is_some() {
+            Some(ExportFormat::ChatGpt)
+        } else if first.get("chat_messages").is_some() {
+            Some(ExportFormat::Claude)
+        } else {
+            None
+        }
+    }
+}
+
+// Parses a whole export file. Conversations without any user or assistant text are left out.
+pub fn parse_export(
+    text: &str,
+    format: Option<ExportFormat>,
+) -> Result<Vec<Session>, Box<dyn std::error::Error>> {
+    let root: Value = serde_json::from_str(text)?;
+    let conversations = root
+        .as_array()
+        .ok_or("Expected a JSON array of conversations, is this a conversations.json file?")?;
+
+    let format = match format {
+        Some(format) => format,
+        None => match ExportFormat::detect(conversations) {
+            Some(format) => format,
+            None if conversations.is_empty() => return Ok(Vec::new()),
+            None => {
+                return Err(
+                    "Could not tell whether this is a ChatGPT or Claude export, use --format"
+                        .into(),
+                )
+            }
+        },
+    };
+
+    Ok(conversations
+        .iter()
+        .filter_map(|conversation| match format {
+            ExportFormat::ChatGpt => from_chatgpt(conversation),
+            ExportFormat::Claude => from_claude(conversation),
+        })
+        .collect())
+}
+
+fn from_chatgpt(conversation: &Value) -> Option<Session> {
+    let mapping = conversation.get("mapping")?.as_object()?;
+    let source_id = conversation
+        .get("conversation_id")
+        .or_else(|| conversation.get("id"))
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let created_at = conversation
+        .get("create_time")
+        .and_then(Value::as_f64)
+        .unwrap_or(0.0) as u64;
+
+    let mut session = empty_session("gpt", created_at, "chatgpt", source_id);
+    session.title = string_field(conversation, "title");
+    session.updated_at = conversation
+        .get("update_time")
+        .and_then(Value::as_f64)
+        .map(|t| t as u64)
+        .unwrap_or(created_at);
+
+    // Walk the node tree depth first, attaching each kept message to its nearest kept ancestor.
+    // System prompts, tool calls and hidden messages are skipped but their children are still visited.
+    let roots: Vec<&String> = mapping
+        .iter()
+        .filter(|(_, node)| node.get("parent").is_none_or(Value::is_null))
+        .map(|(id, _)| id)
+        .collect();
+
+    let mut turn_for_node: HashMap<&str, usize> = HashMap::new();
+    let mut stack: Vec<(&str, Option<usize>)> =
+        roots.iter().rev().map(|id| (id.as_str(), None)).collect();
+
+    while let Some((node_id, parent_turn)) = stack.pop() {
+        let Some(node) = mapping.get(node_id) else {
+            continue;
+        };
+
+        let mut attach_to = parent_turn;
+        if let Some(turn) = chatgpt_turn(node.get("message"), parent_turn) {
+            session.turns.push(turn);
+            attach_to = Some(session.turns.len() - 1);
+            turn_for_node.insert(node_id, session.turns.len() - 1);
+        }
+
+        if let Some(children) = node.get("children").and_then(Value::as_array) {
+            for child in children.iter().rev().filter_map(Value::as_str) {
+                stack.push((child, attach_to));
+            }
+        }
+    }
+
+    // The active branch ends at `current_node`, or the nearest kept message above it
+    let mut current = conversation.get("current_node").and_then(Value::as_str);
+    while let Some(node_id) = current {
+        if let Some(&index) = turn_for_node.get(node_id) {
+            session.head = Some(index);
+            break;
+        }
+        current = mapping
+            .get(node_id)
+            .and_then(|node| node.get("parent"))
+            .and_then(Value::as_str);
+    }
+
+    finish(session)
+}
+
+fn chatgpt_turn(message: Option<&Value>, parent: Option<usize>) -> Option<Turn> {
+    let message = message?;
+    let role = match message.pointer("/author/role").and_then(Value::as_str)? {
+        "user" => Role::User,
+        "assistant" => Role::Assistant,
+        _ => return None,
+    };
+
+    let hidden = message
+        .pointer("/metadata/is_visually_hidden_from_conversation")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if hidden {
+        return None;
+    }
+
+    // Text lives in `content.parts`, which may also hold image or file objects that we skip
+    let content = message
+        .pointer("/content/parts")
+        .and_then(Value::as_array)?
+        .iter()
+        .filter_map(Value::as_str)
+        .collect::<Vec<&str>>()
+        .join("\n");
+    if content.trim().is_empty() {
+        return None;
+    }
+
+    let model = message
+        .pointer("/metadata/model_slug")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    Some(Turn {
+        role,
+        content,
+        timestamp: message
+            .get("create_time")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0) as u64,
+        parent,
+        provider: (role == Role::Assistant).then(|| "gpt".to_string()),
+        model: if role == Role::Assistant { model } else { None },
+        mode: None,
+    })
+}
+
+// Older Claude exports list messages in order, newer ones also record `parent_message_uuid` for edits and retries
+fn from_claude(conversation: &Value) -> Option<Session> {
+    let messages = conversation.get("chat_messages")?.as_array()?;
+    let source_id = string_field(conversation, "uuid");
+    let created_at = iso_field(conversation, "created_at").unwrap_or(0);
+    let model = conversation
+        .get("model")
+        .and_then(Value::as_str)
+        .unwrap_or("claude-web");
+
+    let mut session = empty_session("claude", created_at, "claude", &source_id);
+    session.title = string_field(conversation, "name");
+    session.updated_at = iso_field(conversation, "updated_at").unwrap_or(created_at);
+
+    let mut turn_for_message: HashMap<String, usize> = HashMap::new();
+    let mut previous: Option<usize> = None;
+
+    for message in messages {
+        let role = match message.get("sender").and_then(Value::as_str) {
+            Some("human") => Role::User,
+            Some("assistant") => Role::Assistant,
+            _ => continue,
+        };
+
+        let mut content = string_field(message, "text");
+        if content.trim().is_empty() {
+            content = message
+                .get("content")
+                .and_then(Value::as_array)
+                .map(|blocks| {
+                    blocks
+                        .iter()
+                        .filter(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+                        .filter_map(|block| block.get("text").and_then(Value::as_str))
+                        .collect::<Vec<&str>>()
+                        .join("\n")
+                })
+                .unwrap_or_default();
+        }
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        let parent = match message.get("parent_message_uuid").and_then(Value::as_str) {
+            Some(parent_id) => turn_for_message.get(parent_id).copied(),
+            None => previous,
+        };
+
+        session.turns.push(Turn {
+            role,
+            content,
+            timestamp: iso_field(message, "created_at").unwrap_or(created_at),
+            parent,
+            provider: (role == Role::Assistant).then(|| "claude".to_string()),
+            model: (role == Role::Assistant).then(|| model.to_string()),
+            mode: None,
+        });
+
+        let index = session.turns.len() - 1;
+        turn_for_message.insert(string_field(message, "uuid"), index);
+        previous = Some(index);
+    }
+
+    // The export doesn't mark the active branch, the most recent message is the best guess
+    session.head = session
+        .turns
+        .iter()
+        .enumerate()
+        .max_by_key(|(index, turn)| (turn.timestamp, *index))
+        .map(|(index, _)| index);
+
+    finish(session)
+}
+
+// The web interfaces' model names, like "auto" or "claude-web", aren't API models. They stay on the imported
+// replies, the session itself continues on the provider's default model when it's resumed.
+fn empty_session(provider: &str, created_at: u64, source: &str, source_id: &str) -> Session {
+    let model =
+        from_provider(provider).map_or(String::new(), |model| model.model_name().to_string());
+    let mut session = Session::new(provider, &model, None);
+    session.id = Session::make_id(created_at, fnv1a(source_id));
+    session.created_at = created_at;
+    session.updated_at = created_at;
+    session.source = Some(format!("{}:{}", source, source_id));
+    session
+}
+
+fn finish(mut session: Session) -> Option<Session> {
+    if session.turns.is_empty() {
+        return None;
+    }
+
+    if session.head.is_none() {
+        session.head = Some(session.turns.len() - 1);
+    }
+
+    if session.title.trim().is_empty() {
+        if let Some(first_prompt) = session.turns.iter().find(|turn| turn.role == Role::User) {
+            session.title = title_from_prompt(&first_prompt.content);
+        }
+    }
+
+    Some(session)
+}
+
+fn string_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn iso_field(value: &Value, key: &str) -> Option<u64> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .and_then(|text| timestamp::parse_iso8601(text).ok())
+}
+
+// Stable hash of the source conversation id, so re-importing the same export produces the same session ids
+fn fnv1a(text: &str) -> u32 {
+    text.bytes().fold(0x811c_9dc5u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A prompt that was edited once: the system node is skipped, both user versions hang off the root
+    const CHATGPT: &str = r#"[{
+        "conversation_id": "c1",
+        "title": "Sorting",
+        "create_time": 1714573353.5,
+        "update_time": 1714573400.0,
+        "current_node": "a2",
+        "mapping": {
+            "root": {"parent": null, "children": ["sys"], "message": null},
+            "sys": {"parent": "root", "children": ["u1", "u2"], "message": {
+                "author": {"role": "system"}, "content": {"parts": ["You are ChatGPT"]}}},
+            "u1": {"parent": "sys", "children": ["a1"], "message": {
+                "author": {"role": "user"}, "create_time": 1714573354.0, "content": {"parts": ["sort a list"]}}},
+            "a1": {"parent": "u1", "children": [], "message": {
+                "author": {"role": "assistant"}, "content": {"parts": ["use sorted()"]},
+                "metadata": {"model_slug": "gpt-4o"}}},
+            "u2": {"parent": "sys", "children": ["a2"], "message": {
+                "author": {"role": "user"}, "content": {"parts": ["sort a list in place"]}}},
+            "a2": {"parent": "u2", "children": [], "message": {
+                "author": {"role": "assistant"}, "content": {"parts": ["use list.sort()"]}}}
+        }
+    }]"#;
+
+    // A retried reply, recorded with parent_message_uuid
+    const CLAUDE: &str = r#"[{
+        "uuid": "k1",
+        "name": "",
+        "created_at": "2024-05-01T14:22:33Z",
+        "chat_messages": [
+            {"uuid": "m1", "sender": "human", "text": "hello there", "created_at": "2024-05-01T14:22:33Z"},
+            {"uuid": "m2", "sender": "assistant", "text": "Hi!", "parent_message_uuid": "m1",
+             "created_at": "2024-05-01T14:22:34Z"},
+            {"uuid": "m3", "sender": "assistant", "text": "", "parent_message_uuid": "m1",
+             "content": [{"type": "text", "text": "Hello!"}], "created_at": "2024-05-01T14:22:40Z"}
+        ]
+    }, {
+        "uuid": "k2",
+        "chat_messages": []
+    }]"#;
+
+    #[test]
+    fn imports_chatgpt_branches() {
+        let sessions = parse_export(CHATGPT, None).unwrap();
+        let session = &sessions[0];
+
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(session.title, "Sorting");
+        assert_eq!(session.created_at, 1714573353);
+        assert_eq!(session.turns[1].model.as_deref(), Some("gpt-4o"));
+        assert_eq!(session.source.as_deref(), Some("chatgpt:c1"));
+        assert_eq!(session.turns.len(), 4);
+        assert_eq!(session.branch_tips().len(), 2);
+
+        let path: Vec<&str> = session.path().iter().map(|t| t.content.as_str()).collect();
+        assert_eq!(path, vec!["sort a list in place", "use list.sort()"]);
+    }
+
+    #[test]
+    fn imports_claude_retries_and_skips_empty_conversations() {
+        let sessions = parse_export(CLAUDE, None).unwrap();
+        let session = &sessions[0];
+
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(session.provider, "claude");
+        // No name in the export, the first prompt becomes the title
+        assert!(!session.title.is_empty());
+        assert_eq!(session.turns[1].parent, Some(0));
+        assert_eq!(session.turns[2].parent, Some(0));
+
+        // The newest reply is taken as the active branch
+        let path: Vec<&str> = session.path().iter().map(|t| t.content.as_str()).collect();
+        assert_eq!(path, vec!["hello there", "Hello!"]);
+    }
+
+    #[test]
+    fn imported_sessions_resume_on_an_api_model() {
+        let chatgpt = CHATGPT.replace("gpt-4o", "text-davinci-002-render-sha");
+        for session in parse_export(&chatgpt, None)
+            .unwrap()
+            .into_iter()
+            .chain(parse_export(CLAUDE, None).unwrap())
+        {
+            let mut model = from_provider(&session.provider).unwrap();
+            model.set_model(&session.model);
+            assert!(
+                model.known_models().contains(&model.model_name()),
+                "{} isn't an API model",
+                session.model
+            );
+        }
+
+        // The web model names are kept on the replies
+        let sessions = parse_export(CLAUDE, None).unwrap();
+        assert_eq!(sessions[0].turns[1].model.as_deref(), Some("claude-web"));
+    }
+
+    #[test]
+    fn session_ids_are_stable_across_imports() {
+        let first = parse_export(CLAUDE, None).unwrap();
+        let second = parse_export(CLAUDE, Some(ExportFormat::Claude)).unwrap();
+        assert_eq!(first[0].id, second[0].id);
+    }
+
+    #[test]
+    fn rejects_unknown_exports() {
+        assert!(parse_export(r#"[{"messages": []}]"#, None).is_err());
+        assert!(parse_export(r#"{"mapping": {}}"#, None).is_err());
+        assert!(parse_export("[]", None).unwrap().is_empty());
+    }
+}
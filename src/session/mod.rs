@@ -0,0 +1,254 @@
+// src/session/mod.rs
+
+// A session is one saved conversation: who it was with, what mode it ran in, and every turn exchanged.
+// Sessions are written to disk after each turn so they can be searched and revisited later.
+// Turns form a tree through their parent index, `head` marks the last turn of the active branch.
+
+pub mod export;
+pub mod import;
+pub mod search;
+pub mod store;
+pub mod timestamp;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+impl Role {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Role::User => "You",
+            Role::Assistant => "AI",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Turn {
+    pub role: Role,
+    pub content: String,
+    pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub title: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub provider: String,
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    // Set with /system, replaces the provider's default system prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    // Where an imported session came from, e.g. "chatgpt:<conversation id>"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    // Files sent with /attach, the only ones /apply will change
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<String>,
+    #[serde(default)]
+    pub head: Option<usize>,
+    pub turns: Vec<Turn>,
+}
+
+impl Session {
+    pub fn new(provider: &str, model: &str, mode: Option<&str>) -> Self {
+        let created_at = timestamp::now();
+        let id = Self::make_id(created_at, timestamp::subsec_nanos());
+
+        Session {
+            id,
+            title: String::new(),
+            created_at,
+            updated_at: created_at,
+            provider: provider.to_string(),
+            model: model.to_string(),
+            mode: mode.map(str::to_string),
+            system: None,
+            source: None,
+            attachments: Vec::new(),
+            head: None,
+            turns: Vec::new(),
+        }
+    }
+
+    // Ids read as the creation time, with a short hex suffix to keep sessions from the same second apart
+    pub fn make_id(created_at: u64, salt: u32) -> String {
+        format!(
+            "{}-{:04x}",
+            timestamp::format_compact(created_at),
+            salt & 0xffff
+        )
+    }
+
+    // The turns on the active branch, from the first prompt down to `head`
+    pub fn path(&self) -> Vec<&Turn> {
+        self.path_indices()
+            .into_iter()
+            .map(|index| &self.turns[index])
+            .collect()
+    }
+
+    pub fn path_indices(&self) -> Vec<usize> {
+        match self.head {
+            Some(head) => self.path_to(head),
+            None => Vec::new(),
+        }
+    }
+
+    // Indices from the first prompt down to `index`
+    pub fn path_to(&self, index: usize) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut current = Some(index);
+
+        while let Some(index) = current {
+            path.push(index);
+            current = self.turns[index].parent;
+        }
+
+        path.reverse();
+        path
+    }
+
+    // Index of the most recent prompt on the active branch
+    pub fn last_user_index(&self) -> Option<usize> {
+        self.prompt_indices().last().copied()
+    }
+
+    // Indices of the prompts on the active branch, prompt N in the REPL is element N - 1
+    pub fn prompt_indices(&self) -> Vec<usize> {
+        self.path_indices()
+            .into_iter()
+            .filter(|&index| self.turns[index].role == Role::User)
+            .collect()
+    }
+
+    // Turns nothing else was added after, each one ends a branch
+    pub fn branch_tips(&self) -> Vec<usize> {
+        let mut has_children = vec![false; self.turns.len()];
+        for turn in &self.turns {
+            if let Some(parent) = turn.parent {
+                has_children[parent] = true;
+            }
+        }
+
+        (0..self.turns.len())
+            .filter(|&index| !has_children[index])
+            .collect()
+    }
+
+    // Other turns sharing this turn's parent, i.e. edited prompts or regenerated replies
+    pub fn sibling_count(&self, index: usize) -> usize {
+        let parent = self.turns[index].parent;
+        self.turns
+            .iter()
+            .filter(|turn| turn.parent == parent)
+            .count()
+    }
+
+    // Whether `ancestor` is on the path from the root down to `index`
+    pub fn is_ancestor(&self, ancestor: usize, index: usize) -> bool {
+        let mut current = Some(index);
+        while let Some(i) = current {
+            if i == ancestor {
+                return true;
+            }
+            current = self.turns[i].parent;
+        }
+        false
+    }
+
+    // Each turn records the mode it was sent in, which can differ from the session's current mode
+    pub fn push_user(&mut self, content: &str, mode: Option<&str>) {
+        // The first prompt doubles as the session's title in listings
+        if self.title.is_empty() {
+            self.title = title_from_prompt(content);
+        }
+
+        self.push(Role::User, content, None, None, mode);
+    }
+
+    pub fn push_assistant(&mut self, content: &str, provider: &str, model: &str, mode: Option<&str>) {
+        self.push(Role::Assistant, content, Some(provider), Some(model), mode);
+    }
+
+    fn push(
+        &mut self,
+        role: Role,
+        content: &str,
+        provider: Option<&str>,
+        model: Option<&str>,
+        mode: Option<&str>,
+    ) {
+        let now = timestamp::now();
+        self.turns.push(Turn {
+            role,
+            content: content.to_string(),
+            timestamp: now,
+            parent: self.head,
+            provider: provider.map(str::to_string),
+            model: model.map(str::to_string),
+            mode: mode.map(str::to_string),
+        });
+        self.head = Some(self.turns.len() - 1);
+        self.updated_at = now;
+    }
+}
+
+pub fn title_from_prompt(prompt: &str) -> String {
+    prompt
+        .lines()
+        .next()
+        .unwrap_or("")
+        .chars()
+        .take(60)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3, then a second reply to the first prompt: 1 -> 4
+    fn branched() -> Session {
+        let mut session = Session::new("openai", "gpt-4o", None);
+        session.push_user("first", None);
+        session.push_assistant("reply", "openai", "gpt-4o", None);
+        session.push_user("second", None);
+        session.push_assistant("reply", "openai", "gpt-4o", None);
+        session.head = Some(1);
+        session.push_user("other", None);
+        session
+    }
+
+    #[test]
+    fn branch_tips_are_turns_without_children() {
+        assert_eq!(branched().branch_tips(), vec![3, 4]);
+    }
+
+    #[test]
+    fn is_ancestor_walks_up_from_the_index() {
+        let session = branched();
+        assert!(session.is_ancestor(1, 3));
+        assert!(session.is_ancestor(1, 4));
+        assert!(session.is_ancestor(3, 3));
+        assert!(!session.is_ancestor(2, 4));
+        assert!(!session.is_ancestor(3, 1));
+    }
+}
@@ -0,0 +1,306 @@
+// src/session/search.rs
+
+// Full-text search over saved sessions.
+// Every turn of every session is treated as a document and indexed by its lowercase word tokens.
+// Results are ranked with a tf-idf score, boosted by how many of the query terms a turn contains.
+
+use super::{Role, Session};
+use crate::text_formatter::color_text;
+use std::collections::HashMap;
+
+const SNIPPET_BEFORE: usize = 60;
+const SNIPPET_AFTER: usize = 120;
+
+#[derive(Default)]
+pub struct SearchFilter {
+    pub provider: Option<String>,
+    pub mode: Option<String>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
+pub struct SearchHit {
+    pub session_id: String,
+    pub session_title: String,
+    pub provider: String,
+    pub mode: Option<String>,
+    pub role: Role,
+    pub timestamp: u64,
+    pub score: f64,
+    pub snippet: String,
+}
+
+struct Posting {
+    doc: usize,
+    term_frequency: u32,
+}
+
+struct Document {
+    session: usize,
+    turn: usize,
+}
+
+pub struct SearchIndex<'a> {
+    sessions: &'a [Session],
+    documents: Vec<Document>,
+    postings: HashMap<String, Vec<Posting>>,
+}
+
+impl<'a> SearchIndex<'a> {
+    pub fn build(sessions: &'a [Session]) -> Self {
+        let mut documents = Vec::new();
+        let mut postings: HashMap<String, Vec<Posting>> = HashMap::new();
+
+        for (session_index, session) in sessions.iter().enumerate() {
+            for (turn_index, turn) in session.turns.iter().enumerate() {
+                let doc = documents.len();
+                documents.push(Document {
+                    session: session_index,
+                    turn: turn_index,
+                });
+
+                let mut counts: HashMap<String, u32> = HashMap::new();
+                for (_, _, token) in tokenize(&turn.content) {
+                    *counts.entry(token).or_insert(0) += 1;
+                }
+
+                for (token, term_frequency) in counts {
+                    postings.entry(token).or_default().push(Posting {
+                        doc,
+                        term_frequency,
+                    });
+                }
+            }
+        }
+
+        SearchIndex {
+            sessions,
+            documents,
+            postings,
+        }
+    }
+
+    pub fn search(&self, query: &str, filter: &SearchFilter, limit: usize) -> Vec<SearchHit> {
+        let mut terms: Vec<String> = tokenize(query).into_iter().map(|(_, _, t)| t).collect();
+        terms.sort();
+        terms.dedup();
+
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        // Accumulate tf-idf per document, remembering how many distinct terms matched
+        let total_docs = self.documents.len() as f64;
+        let mut scores: HashMap<usize, (f64, usize)> = HashMap::new();
+
+        for term in &terms {
+            let Some(postings) = self.postings.get(term) else {
+                continue;
+            };
+
+            let idf = (1.0 + total_docs / postings.len() as f64).ln();
+            for posting in postings {
+                let entry = scores.entry(posting.doc).or_insert((0.0, 0));
+                entry.0 += (1.0 + (posting.term_frequency as f64).ln()) * idf;
+                entry.1 += 1;
+            }
+        }
+
+        let mut hits: Vec<SearchHit> = scores
+            .into_iter()
+            .filter(|(doc, _)| self.matches_filter(&self.documents[*doc], filter))
+            .map(|(doc, (score, matched))| {
+                let document = &self.documents[doc];
+                let session = &self.sessions[document.session];
+                let turn = &session.turns[document.turn];
+
+                SearchHit {
+                    session_id: session.id.clone(),
+                    session_title: session.title.clone(),
+                    provider: turn
+                        .provider
+                        .clone()
+                        .unwrap_or_else(|| session.provider.clone()),
+                    mode: turn.mode.clone(),
+                    role: turn.role,
+                    timestamp: turn.timestamp,
+                    score: score * matched as f64 / terms.len() as f64,
+                    snippet: build_snippet(&turn.content, &terms),
+                }
+            })
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.timestamp.cmp(&a.timestamp))
+        });
+        hits.truncate(limit);
+        hits
+    }
+
+    fn matches_filter(&self, document: &Document, filter: &SearchFilter) -> bool {
+        let session = &self.sessions[document.session];
+        let turn = &session.turns[document.turn];
+
+        if let Some(provider) = &filter.provider {
+            let turn_provider = turn.provider.as_deref().unwrap_or(&session.provider);
+            if !turn_provider.eq_ignore_ascii_case(provider) {
+                return false;
+            }
+        }
+
+        if let Some(mode) = &filter.mode {
+            // Stacked modes like "short+command" match each of their parts
+            let turn_mode = turn.mode.as_deref().unwrap_or("none");
+            if !turn_mode.eq_ignore_ascii_case(mode)
+                && !turn_mode.split('+').any(|part| part.eq_ignore_ascii_case(mode))
+            {
+                return false;
+            }
+        }
+
+        filter.since.is_none_or(|since| turn.timestamp >= since)
+            && filter.until.is_none_or(|until| turn.timestamp <= until)
+    }
+}
+
+// Splits text into lowercase alphanumeric tokens, keeping each token's byte range in the original text
+fn tokenize(text: &str) -> Vec<(usize, usize, String)> {
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (index, ch) in text.char_indices() {
+        if ch.is_alphanumeric() || ch == '_' {
+            start.get_or_insert(index);
+        } else if let Some(token_start) = start.take() {
+            tokens.push((token_start, index, text[token_start..index].to_lowercase()));
+        }
+    }
+
+    if let Some(token_start) = start {
+        tokens.push((token_start, text.len(), text[token_start..].to_lowercase()));
+    }
+
+    tokens
+}
+
+// Cuts a window of text around the first matching term and highlights every match inside it
+fn build_snippet(content: &str, terms: &[String]) -> String {
+    let matches: Vec<(usize, usize)> = tokenize(content)
+        .into_iter()
+        .filter(|(_, _, token)| terms.contains(token))
+        .map(|(start, end, _)| (start, end))
+        .collect();
+
+    let Some(&(first_start, first_end)) = matches.first() else {
+        return String::new();
+    };
+
+    let mut window_start = first_start.saturating_sub(SNIPPET_BEFORE);
+    while !content.is_char_boundary(window_start) {
+        window_start -= 1;
+    }
+
+    let mut window_end = (first_end + SNIPPET_AFTER).min(content.len());
+    while !content.is_char_boundary(window_end) {
+        window_end += 1;
+    }
+
+    let mut snippet = String::new();
+    if window_start > 0 {
+        snippet.push_str("...");
+    }
+
+    let mut cursor = window_start;
+    for &(start, end) in matches
+        .iter()
+        .filter(|(s, e)| *s >= window_start && *e <= window_end)
+    {
+        snippet.push_str(&content[cursor..start]);
+        snippet.push_str(&color_text(&content[start..end], "yellow"));
+        cursor = end;
+    }
+    snippet.push_str(&content[cursor..window_end]);
+
+    if window_end < content.len() {
+        snippet.push_str("...");
+    }
+
+    // Keep each hit on a single line in the results list
+    snippet.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(prompt: &str, reply: &str, mode: Option<&str>) -> Session {
+        let mut session = Session::new("gpt", "gpt-4o", mode);
+        session.push_user(prompt, mode);
+        session.push_assistant(reply, "gpt", "gpt-4o", mode);
+        session
+    }
+
+    #[test]
+    fn tokenize_lowercases_and_keeps_byte_ranges() {
+        let tokens = tokenize("Rsync --dry_run, ünï 42!");
+        let words: Vec<&str> = tokens.iter().map(|(_, _, t)| t.as_str()).collect();
+        assert_eq!(words, vec!["rsync", "dry_run", "ünï", "42"]);
+
+        let (start, end, _) = tokens[2];
+        assert_eq!(&"Rsync --dry_run, ünï 42!"[start..end], "ünï");
+    }
+
+    #[test]
+    fn snippet_highlights_matches_on_one_line() {
+        let snippet = build_snippet("Use\nrsync -a\nto copy", &["rsync".to_string()]);
+        assert_eq!(
+            snippet,
+            format!("Use {} -a to copy", color_text("rsync", "yellow"))
+        );
+    }
+
+    #[test]
+    fn snippet_cuts_long_text_at_char_boundaries() {
+        let content = format!("{} needle {}", "é".repeat(100), "ö".repeat(200));
+        let snippet = build_snippet(&content, &["needle".to_string()]);
+        assert!(snippet.starts_with("..."));
+        assert!(snippet.ends_with("..."));
+        assert!(snippet.contains(&color_text("needle", "yellow")));
+    }
+
+    #[test]
+    fn ranks_turns_matching_more_terms_first() {
+        let sessions = vec![
+            session("copy files", "use cp", None),
+            session("copy files with rsync", "rsync -a src dst", None),
+        ];
+        let index = SearchIndex::build(&sessions);
+        let hits = index.search("rsync copy", &SearchFilter::default(), 10);
+
+        assert_eq!(hits[0].session_id, sessions[1].id);
+        assert_eq!(hits[0].role, Role::User);
+        assert!(index
+            .search("nothing here", &SearchFilter::default(), 10)
+            .is_empty());
+    }
+
+    #[test]
+    fn filters_by_stacked_mode() {
+        let sessions = vec![
+            session("list files", "ls", Some("short+command")),
+            session("list files", "ls -la", None),
+        ];
+        let index = SearchIndex::build(&sessions);
+        let filter = SearchFilter {
+            mode: Some("command".to_string()),
+            ..SearchFilter::default()
+        };
+        let hits = index.search("files", &filter, 10);
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, sessions[0].id);
+    }
+}
@@ -0,0 +1,83 @@
+// src/session/store.rs
+
+// Reads and writes sessions as one JSON file per session.
+// Files live in $CONSOLE_AI_CHAT_HOME/sessions, defaulting to ~/.console_ai_chat/sessions.
+
+use super::Session;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+pub fn data_dir() -> PathBuf {
+    if let Ok(dir) = env::var("CONSOLE_AI_CHAT_HOME") {
+        return PathBuf::from(dir);
+    }
+
+    let home = env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".console_ai_chat")
+}
+
+pub fn sessions_dir() -> PathBuf {
+    data_dir().join("sessions")
+}
+
+pub fn save(session: &Session) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = sessions_dir();
+    fs::create_dir_all(&dir)?;
+
+    // Write to a temporary file first so an interrupted save never leaves a truncated session behind
+    let path = dir.join(format!("{}.json", session.id));
+    let tmp_path = dir.join(format!("{}.json.tmp", session.id));
+    fs::write(&tmp_path, serde_json::to_string_pretty(session)?)?;
+    fs::rename(tmp_path, path)?;
+
+    Ok(())
+}
+
+// Loads a session by its full id or by a unique prefix of it
+pub fn load(id: &str) -> Result<Session, Box<dyn std::error::Error>> {
+    let exact = sessions_dir().join(format!("{}.json", id));
+    if exact.exists() {
+        return Ok(serde_json::from_str(&fs::read_to_string(exact)?)?);
+    }
+
+    let matches: Vec<Session> = load_all()?
+        .into_iter()
+        .filter(|session| session.id.starts_with(id))
+        .collect();
+
+    match matches.len() {
+        0 => Err(format!("No saved session matches '{}'", id).into()),
+        1 => Ok(matches.into_iter().next().unwrap()),
+        n => Err(format!("'{}' matches {} sessions, please use a longer id", id, n).into()),
+    }
+}
+
+// Loads every saved session, newest first. Files that fail to parse are skipped with a warning.
+pub fn load_all() -> Result<Vec<Session>, Box<dyn std::error::Error>> {
+    let dir = sessions_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut sessions = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+
+        match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| serde_json::from_str::<Session>(&text).map_err(|e| e.to_string()))
+        {
+            Ok(session) => sessions.push(session),
+            Err(e) => eprintln!("Skipping unreadable session {}: {}", path.display(), e),
+        }
+    }
+
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.updated_at));
+    Ok(sessions)
+}
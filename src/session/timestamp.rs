@@ -0,0 +1,179 @@
+// src/session/timestamp.rs
+
+// Small helpers for working with unix timestamps without pulling in a date/time crate.
+// Sessions store seconds since the epoch, these functions convert to and from UTC calendar dates.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Nanosecond part of the current time, used to keep ids created in the same second apart
+pub fn subsec_nanos() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0)
+}
+
+// Formats a timestamp as "YYYY-MM-DD HH:MM" (UTC)
+pub fn format_datetime(timestamp: u64) -> String {
+    let (year, month, day, hour, minute, _) = to_civil(timestamp);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year, month, day, hour, minute
+    )
+}
+
+// Formats a timestamp as "YYYYMMDD-HHMMSS" (UTC), used as the readable part of session ids
+pub fn format_compact(timestamp: u64) -> String {
+    let (year, month, day, hour, minute, second) = to_civil(timestamp);
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year, month, day, hour, minute, second
+    )
+}
+
+// Parses "YYYY-MM-DD" into the timestamp of midnight (UTC) on that day
+pub fn parse_date(date: &str) -> Result<u64, String> {
+    let parts: Vec<&str> = date.trim().split('-').collect();
+    let invalid = || format!("Invalid date '{}', expected YYYY-MM-DD", date);
+
+    if parts.len() != 3 {
+        return Err(invalid());
+    }
+
+    let year: i64 = parts[0].parse().map_err(|_| invalid())?;
+    let month: i64 = parts[1].parse().map_err(|_| invalid())?;
+    let day: i64 = parts[2].parse().map_err(|_| invalid())?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || year < 1970 {
+        return Err(invalid());
+    }
+
+    Ok((days_from_civil(year, month, day) * SECONDS_PER_DAY) as u64)
+}
+
+// Parses "YYYY-MM-DD" into the last second of that day, so date ranges are inclusive
+pub fn parse_date_end(date: &str) -> Result<u64, String> {
+    Ok(parse_date(date)? + SECONDS_PER_DAY as u64 - 1)
+}
+
+// Parses RFC 3339 timestamps such as "2024-05-01T14:22:33.123456Z" or "2024-05-01T16:22:33+02:00"
+pub fn parse_iso8601(text: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid timestamp '{}'", text);
+    let (date, time) = text.split_once(['T', ' ']).ok_or_else(invalid)?;
+    let midnight = parse_date(date)? as i64;
+
+    // Split the clock time from the UTC offset, which is either "Z" or "+HH:MM" / "-HH:MM"
+    let (clock, offset_seconds) = if let Some(clock) = time.strip_suffix('Z') {
+        (clock, 0)
+    } else if let Some(index) = time.rfind(['+', '-']) {
+        let (clock, offset) = time.split_at(index);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (hours, minutes) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+        let hours: i64 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i64 = minutes.parse().map_err(|_| invalid())?;
+        (clock, sign * (hours * 3600 + minutes * 60))
+    } else {
+        (time, 0)
+    };
+
+    let mut fields = clock.split(':');
+    let mut next_field = || -> Result<i64, String> {
+        let field = fields.next().unwrap_or("0");
+        let whole = field.split('.').next().unwrap_or("0");
+        whole.parse().map_err(|_| invalid())
+    };
+    let seconds_of_day = next_field()? * 3600 + next_field()? * 60 + next_field()?;
+
+    Ok((midnight + seconds_of_day - offset_seconds).max(0) as u64)
+}
+
+fn to_civil(timestamp: u64) -> (i64, i64, i64, i64, i64, i64) {
+    let timestamp = timestamp as i64;
+    let days = timestamp.div_euclid(SECONDS_PER_DAY);
+    let seconds_of_day = timestamp.rem_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+
+    (
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        (seconds_of_day % 3600) / 60,
+        seconds_of_day % 60,
+    )
+}
+
+// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's algorithm)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_iso8601_in_utc_and_with_offsets() {
+        assert_eq!(parse_iso8601("2024-05-01T14:22:33Z"), Ok(1714573353));
+        assert_eq!(parse_iso8601("2024-05-01T14:22:33.123456Z"), Ok(1714573353));
+        assert_eq!(parse_iso8601("2024-05-01T16:22:33+02:00"), Ok(1714573353));
+        assert_eq!(parse_iso8601("2024-05-01 09:52:33-04:30"), Ok(1714573353));
+        // An offset can move the time into the previous day
+        assert_eq!(parse_iso8601("2024-01-01T01:30:00+02:00"), Ok(1704065400));
+    }
+
+    #[test]
+    fn rejects_invalid_timestamps() {
+        assert!(parse_iso8601("2024-05-01").is_err());
+        assert!(parse_iso8601("2024-13-01T00:00:00Z").is_err());
+        assert!(parse_iso8601("2024-05-01Tnoon").is_err());
+    }
+
+    #[test]
+    fn parses_dates_as_whole_days() {
+        assert_eq!(parse_date("2024-02-29"), Ok(1709164800));
+        assert_eq!(parse_date_end("2024-02-29"), Ok(1709164800 + 86_399));
+        assert!(parse_date("29-02-2024").is_err());
+        assert!(parse_date("1969-12-31").is_err());
+    }
+
+    #[test]
+    fn formats_timestamps_in_utc() {
+        assert_eq!(format_datetime(1714573353), "2024-05-01 14:22");
+        assert_eq!(format_compact(1714573353), "20240501-142233");
+        assert_eq!(format_datetime(1704065400), "2023-12-31 23:30");
+    }
+}
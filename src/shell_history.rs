@@ -0,0 +1,167 @@
+// src/shell_history.rs
+
+// Reads the most recent commands from the user's shell history file, for `chat fix`.
+// Supports bash, zsh (plain and extended format) and fish. $HISTFILE is honored for bash and zsh.
+//
+// Bash only writes its history when the shell exits, so the last command is missing unless
+// `PROMPT_COMMAND="history -a"` is set. Zsh needs INC_APPEND_HISTORY or SHARE_HISTORY for the same reason.
+
+use crate::environment::detect_shell;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// The history file of the user's shell and the commands in it, oldest first
+pub fn read_history() -> Result<Vec<String>, String> {
+    let shell = detect_shell().unwrap_or_else(|| "bash".to_string());
+    let home = env::var("HOME").map_err(|_| "HOME is not set".to_string())?;
+
+    let (path, parse): (PathBuf, fn(&str) -> Vec<String>) = match shell.as_str() {
+        "zsh" => (
+            histfile().unwrap_or_else(|| PathBuf::from(&home).join(".zsh_history")),
+            parse_zsh,
+        ),
+        "fish" => {
+            let data = env::var("XDG_DATA_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from(&home).join(".local/share"));
+            (data.join("fish/fish_history"), parse_fish)
+        }
+        _ => (
+            histfile().unwrap_or_else(|| PathBuf::from(&home).join(".bash_history")),
+            parse_bash,
+        ),
+    };
+
+    // History files can hold bytes that aren't UTF-8, zsh in particular
+    let bytes = fs::read(&path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    Ok(parse(&String::from_utf8_lossy(&bytes)))
+}
+
+fn histfile() -> Option<PathBuf> {
+    env::var("HISTFILE")
+        .ok()
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+// One command per line, with "#1700000000" timestamp lines when HISTTIMEFORMAT is set
+fn parse_bash(text: &str) -> Vec<String> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter(|line| !(line.starts_with('#') && line[1..].chars().all(|c| c.is_ascii_digit())))
+        .map(str::to_string)
+        .collect()
+}
+
+// Plain lines, or ": 1700000000:0;command" with extended history. A trailing backslash continues the command.
+fn parse_zsh(text: &str) -> Vec<String> {
+    let mut commands: Vec<String> = Vec::new();
+    let mut continuing = false;
+
+    for line in text.lines() {
+        if continuing {
+            if let Some(last) = commands.last_mut() {
+                last.push('\n');
+                last.push_str(line.strip_suffix('\\').unwrap_or(line));
+            }
+        } else {
+            let command = match line.strip_prefix(": ") {
+                Some(rest) => rest.split_once(';').map_or(line, |(_, command)| command),
+                None => line,
+            };
+            commands.push(command.strip_suffix('\\').unwrap_or(command).to_string());
+        }
+        continuing = line.ends_with('\\');
+    }
+
+    commands.retain(|command| !command.trim().is_empty());
+    commands
+}
+
+// "- cmd: ls -la" entries followed by "  when: 1700000000". Newlines and backslashes are escaped.
+fn parse_fish(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| line.strip_prefix("- cmd: "))
+        .map(unescape_fish)
+        .collect()
+}
+
+// Escapes are read left to right, so an escaped backslash before an n stays a backslash and an n
+fn unescape_fish(command: &str) -> String {
+    let mut text = String::new();
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                text.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                text.push('\\');
+                chars.next();
+            }
+            _ => text.push(c),
+        }
+    }
+    text
+}
+
+// The last command that isn't a call to this tool, which is usually the `chat fix` being run now
+pub fn last_command(history: &[String]) -> Option<&str> {
+    let program = env::args()
+        .next()
+        .and_then(|path| {
+            PathBuf::from(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+
+    history.iter().rev().map(String::as_str).find(|command| {
+        let first = command.split_whitespace().next().unwrap_or("");
+        let name = first.rsplit('/').next().unwrap_or(first);
+        name != "chat" && name != program && name != "console_ai_chat"
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bash_skips_timestamps_and_blank_lines() {
+        let history = "ls\n#1700000000\ngit status\n\n# a comment\n";
+        assert_eq!(parse_bash(history), vec!["ls", "git status", "# a comment"]);
+    }
+
+    #[test]
+    fn zsh_reads_extended_history_and_continuations() {
+        let history = ": 1700000000:0;cd /tmp\n\
+                       : 1700000001:0;echo one \\\n\
+                       two\n\
+                       plain command\n\
+                       : 1700000002:0;a;b\n";
+        assert_eq!(
+            parse_zsh(history),
+            vec!["cd /tmp", "echo one \ntwo", "plain command", "a;b"]
+        );
+    }
+
+    #[test]
+    fn fish_unescapes_commands() {
+        let history = "- cmd: echo a\\nb\n  when: 1700000000\n\
+                       - cmd: printf 'x\\\\ny'\n  when: 1700000001\n";
+        assert_eq!(parse_fish(history), vec!["echo a\nb", "printf 'x\\ny'"]);
+    }
+
+    #[test]
+    fn last_command_skips_calls_to_this_tool() {
+        let history: Vec<String> = ["make", "chat fix", "/usr/local/bin/console_ai_chat fix"]
+            .iter()
+            .map(|command| command.to_string())
+            .collect();
+        assert_eq!(last_command(&history), Some("make"));
+        assert_eq!(last_command(&history[1..]), None);
+    }
+}
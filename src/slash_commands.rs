@@ -0,0 +1,845 @@
+// src/slash_commands.rs
+
+// Commands typed into the conversation prompt that start with a slash, e.g. `/mode short` or `/retry`.
+// They act on the conversation locally and are never sent to the model.
+
+use crate::clipboard::copy_to_clipboard;
+use crate::code_blocks::{code_block, resolve_path};
+use crate::code_runner::run_code_block;
+use crate::commands::explain::split_words;
+use crate::conversation::{save_session, Conversation};
+use crate::file_edits::apply::plan;
+use crate::file_edits::parse::parse_edits;
+use crate::file_edits::{backup, language_for_path};
+use crate::line_editor::compose_in_editor;
+use crate::models::from_provider;
+use crate::prompt::ask_yes_no;
+use crate::response_types::{all_modifiers, post_process_for, ModifierStack};
+use crate::session::{export, store, timestamp, Role, Session};
+use crate::step_runner::run_response_steps;
+use crate::syntax_highlighter::highlight_diff;
+use crate::text_formatter::{color_text, format_error};
+use std::fs;
+use std::path::Path;
+
+pub enum Outcome {
+    Continue,
+    // Send this text to the model as the next prompt
+    Send(String),
+    Quit,
+}
+
+struct SlashCommand {
+    name: &'static str,
+    usage: &'static str,
+    description: &'static str,
+}
+
+const COMMANDS: &[SlashCommand] = &[
+    SlashCommand {
+        name: "help",
+        usage: "/help",
+        description: "Show this list",
+    },
+    SlashCommand {
+        name: "clear",
+        usage: "/clear",
+        description: "Start a new session with the same settings",
+    },
+    SlashCommand {
+        name: "retry",
+        usage: "/retry",
+        description: "Ask for a new reply to the last prompt",
+    },
+    SlashCommand {
+        name: "regenerate",
+        usage: "/regenerate [N]",
+        description: "Ask for a new reply to prompt N, as a new branch",
+    },
+    SlashCommand {
+        name: "undo",
+        usage: "/undo",
+        description: "Remove the last prompt and its reply",
+    },
+    SlashCommand {
+        name: "model",
+        usage: "/model <name>",
+        description: "Switch the model used by the current provider",
+    },
+    SlashCommand {
+        name: "provider",
+        usage: "/provider <gpt|claude>",
+        description: "Switch provider, keeping the conversation",
+    },
+    SlashCommand {
+        name: "mode",
+        usage: "/mode <name...|none>",
+        description: "Change the response style for the next prompts",
+    },
+    SlashCommand {
+        name: "system",
+        usage: "/system <text>",
+        description: "Set the system prompt",
+    },
+    SlashCommand {
+        name: "save",
+        usage: "/save [file.md|file.html]",
+        description: "Save the session, or export it to a file",
+    },
+    SlashCommand {
+        name: "save",
+        usage: "/save code N [path]",
+        description: "Save code block N of the last response to a file",
+    },
+    SlashCommand {
+        name: "load",
+        usage: "/load <session-id>",
+        description: "Continue a saved session",
+    },
+    SlashCommand {
+        name: "tokens",
+        usage: "/tokens",
+        description: "Estimate the size of the conversation in tokens",
+    },
+    SlashCommand {
+        name: "copy",
+        usage: "/copy",
+        description: "Copy the last response to the clipboard",
+    },
+    SlashCommand {
+        name: "copy",
+        usage: "/copy code N",
+        description: "Copy code block N of the last response",
+    },
+    SlashCommand {
+        name: "steps",
+        usage: "/steps",
+        description: "Run the commands in the last response one by one",
+    },
+    SlashCommand {
+        name: "run",
+        usage: "/run N",
+        description: "Run python or bash code block N of the last response",
+    },
+    SlashCommand {
+        name: "attach",
+        usage: "/attach [file...|clear]",
+        description: "Send files with the next prompt, list attached files, or forget them",
+    },
+    SlashCommand {
+        name: "apply",
+        usage: "/apply",
+        description: "Apply the diff or search/replace blocks of the last response",
+    },
+    SlashCommand {
+        name: "apply",
+        usage: "/apply undo",
+        description: "Put back the files changed by the last /apply",
+    },
+    SlashCommand {
+        name: "edit",
+        usage: "/edit [text]",
+        description: "Write the next prompt in $EDITOR",
+    },
+    SlashCommand {
+        name: "edit",
+        usage: "/edit N [text]",
+        description: "Rewrite prompt N and continue from there, as a new branch",
+    },
+    SlashCommand {
+        name: "turns",
+        usage: "/turns",
+        description: "List the prompts on the current branch",
+    },
+    SlashCommand {
+        name: "branches",
+        usage: "/branches",
+        description: "List every branch of the conversation",
+    },
+    SlashCommand {
+        name: "checkout",
+        usage: "/checkout N",
+        description: "Switch to branch N from /branches",
+    },
+    SlashCommand {
+        name: "quit",
+        usage: "/quit",
+        description: "End the conversation",
+    },
+];
+
+// A slash command is a slash followed by a word, so prompts like "/etc/hosts is empty?" still reach the model
+pub fn is_slash_command(input: &str) -> bool {
+    let name = input.split_whitespace().next().unwrap_or("");
+    name.len() > 1
+        && name.starts_with('/')
+        && name[1..]
+            .chars()
+            .all(|c| c.is_ascii_alphabetic() || c == '-')
+}
+
+pub async fn handle(conversation: &mut Conversation, input: &str) -> Outcome {
+    let (name, argument) = match input[1..].split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (&input[1..], ""),
+    };
+
+    let result = match name.to_lowercase().as_str() {
+        "help" => help(),
+        "clear" => clear(conversation),
+        "retry" => retry(conversation).await,
+        "regenerate" => regenerate(conversation, argument).await,
+        "turns" => turns(conversation),
+        "branches" => branches(conversation),
+        "checkout" => checkout(conversation, argument),
+        "undo" => undo(conversation),
+        "model" => model(conversation, argument),
+        "provider" => provider(conversation, argument),
+        "mode" => mode(conversation, argument),
+        "system" => system(conversation, argument),
+        "save" => save(conversation, argument),
+        "load" => load(conversation, argument),
+        "tokens" => tokens(conversation),
+        "copy" => copy(conversation, argument),
+        "steps" => steps(conversation).await,
+        "run" => run_code_block(conversation, argument).await,
+        "attach" => attach(conversation, argument),
+        "apply" => apply(conversation, argument),
+        "edit" => return edit(conversation, argument).await,
+        "quit" | "exit" => return Outcome::Quit,
+        unknown => Err(unknown_command(unknown).into()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", format_error(&e.to_string()));
+    }
+    Outcome::Continue
+}
+
+type CommandResult = Result<(), Box<dyn std::error::Error>>;
+
+fn help() -> CommandResult {
+    println!();
+    for command in COMMANDS {
+        // Pad before coloring, the escape codes would otherwise count towards the width
+        let usage = format!("{:<28}", command.usage);
+        println!("  {} {}", color_text(&usage, "cyan"), command.description);
+    }
+    println!("\n  Start a prompt with a style's flag, like -s or -c, to use it for that prompt only, -n for the default style. Flags can be stacked like -s -c.");
+    println!("  Start a prompt with // to send text that begins with a slash, or with \\ to send text that begins with a flag, like \\-r.");
+    println!(
+        "  Alt-Enter, Ctrl-J or a trailing \\ starts a new line, Ctrl-R searches earlier prompts."
+    );
+    Ok(())
+}
+
+fn clear(conversation: &mut Conversation) -> CommandResult {
+    let previous = &conversation.session;
+    let mut session = Session::new(
+        &previous.provider,
+        &previous.model,
+        previous.mode.as_deref(),
+    );
+    session.system = previous.system.clone();
+
+    conversation.session = session;
+    println!("Started a new session ({}).", conversation.session.id);
+    Ok(())
+}
+
+async fn retry(conversation: &mut Conversation) -> CommandResult {
+    let user_index = conversation
+        .session
+        .last_user_index()
+        .ok_or("There is no prompt to retry yet")?;
+
+    let response = conversation.regenerate(user_index).await?;
+    conversation.print_response(&response);
+    Ok(())
+}
+
+async fn regenerate(conversation: &mut Conversation, argument: &str) -> CommandResult {
+    if argument.is_empty() {
+        return retry(conversation).await;
+    }
+
+    let user_index = prompt_index(conversation, argument)?;
+    let response = conversation.regenerate(user_index).await?;
+    conversation.print_response(&response);
+    Ok(())
+}
+
+fn turns(conversation: &mut Conversation) -> CommandResult {
+    let session = &conversation.session;
+    let prompts = session.prompt_indices();
+    if prompts.is_empty() {
+        println!("No prompts yet.");
+        return Ok(());
+    }
+
+    for (number, &index) in prompts.iter().enumerate() {
+        let versions = session.sibling_count(index);
+        let note = if versions > 1 {
+            color_text(&format!(" ({} versions)", versions), "yellow")
+        } else {
+            String::new()
+        };
+        println!(
+            "  {}. {}{}",
+            number + 1,
+            preview(&session.turns[index].content),
+            note
+        );
+    }
+    Ok(())
+}
+
+fn branches(conversation: &mut Conversation) -> CommandResult {
+    let session = &conversation.session;
+    let tips = session.branch_tips();
+    if tips.is_empty() {
+        println!("No branches yet.");
+        return Ok(());
+    }
+
+    for (number, &tip) in tips.iter().enumerate() {
+        // The head is on this branch when it sits anywhere on the way down to the tip
+        let active = session
+            .head
+            .is_some_and(|head| session.is_ancestor(head, tip));
+        let marker = if active {
+            color_text("*", "green")
+        } else {
+            " ".to_string()
+        };
+
+        // Describe a branch by its last prompt, which is where it differs from its siblings
+        let mut last_prompt = Some(tip);
+        while let Some(index) = last_prompt.filter(|&i| session.turns[i].role != Role::User) {
+            last_prompt = session.turns[index].parent;
+        }
+        let depth = session.path_to(tip).len();
+
+        println!(
+            "{} {}. {} ({} turns, {})",
+            marker,
+            number + 1,
+            last_prompt.map_or(String::new(), |i| preview(&session.turns[i].content)),
+            depth,
+            timestamp::format_datetime(session.turns[tip].timestamp)
+        );
+    }
+    Ok(())
+}
+
+fn checkout(conversation: &mut Conversation, argument: &str) -> CommandResult {
+    let tips = conversation.session.branch_tips();
+    let tip = argument
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| number.checked_sub(1))
+        .and_then(|i| tips.get(i).copied())
+        .ok_or_else(|| {
+            format!(
+                "Usage: /checkout N, where N is between 1 and {}",
+                tips.len()
+            )
+        })?;
+
+    conversation.session.head = Some(tip);
+    save_session(&conversation.session);
+
+    let turn = &conversation.session.turns[tip];
+    println!("Switched branch.");
+    if turn.role == Role::Assistant {
+        let response = post_process_for(turn.mode.as_deref(), &turn.content);
+        conversation.print_response(&response);
+    }
+    Ok(())
+}
+
+fn undo(conversation: &mut Conversation) -> CommandResult {
+    let user_index = conversation
+        .session
+        .last_user_index()
+        .ok_or("There is nothing to undo")?;
+
+    // Move the head back above the last prompt. The turns stay in the saved file as an abandoned branch.
+    conversation.session.head = conversation.session.turns[user_index].parent;
+    save_session(&conversation.session);
+
+    println!(
+        "Removed: {}",
+        conversation.session.turns[user_index].content
+    );
+    Ok(())
+}
+
+fn model(conversation: &mut Conversation, name: &str) -> CommandResult {
+    if name.is_empty() {
+        println!(
+            "Current model: {} ({})",
+            conversation.model.model_name(),
+            conversation.model.provider()
+        );
+        return Ok(());
+    }
+
+    // Newer models than the list knows about are allowed, but most unknown names are typos
+    let known = conversation.model.known_models();
+    if !known.contains(&name) {
+        println!(
+            "{}",
+            color_text(
+                &format!(
+                    "{} isn't a known {} model ({}), the API may reject it.",
+                    name,
+                    conversation.model.provider(),
+                    known.join(", ")
+                ),
+                "yellow"
+            )
+        );
+    }
+
+    conversation.session.model = name.to_string();
+    conversation.model.set_model(name);
+    save_session(&conversation.session);
+    println!("Now using {}.", name);
+    Ok(())
+}
+
+fn provider(conversation: &mut Conversation, name: &str) -> CommandResult {
+    let name = match name {
+        "" => {
+            println!("Current provider: {}", conversation.model.provider());
+            return Ok(());
+        }
+        "cl" => "claude",
+        other => other,
+    };
+
+    // Each provider starts on its own default model. The history lives in the session, so the new
+    // provider receives the whole conversation with the next prompt.
+    let fresh = from_provider(name)
+        .ok_or_else(|| format!("Unknown provider '{}', use gpt or claude", name))?;
+    conversation.session.provider = name.to_string();
+    conversation.session.model = fresh.model_name().to_string();
+    conversation.model = fresh;
+    save_session(&conversation.session);
+
+    println!("Now using {} ({}).", name, conversation.session.model);
+    Ok(())
+}
+
+fn mode(conversation: &mut Conversation, names: &str) -> CommandResult {
+    // Accept each mode's name or its flag, e.g. "short", "-s" or "s". Several can be stacked: "short command" or "short+command"
+    let mut stack = ModifierStack::default();
+    for name in names.split(|c: char| c == '+' || c.is_whitespace()).filter(|n| !n.is_empty()) {
+        if matches!(name, "none" | "-n" | "n") {
+            stack = ModifierStack::default();
+            continue;
+        }
+
+        let modifier = all_modifiers()
+            .into_iter()
+            .find(|m| m.name() == name || m.flag() == name || m.flag().trim_start_matches('-') == name)
+            .ok_or_else(|| {
+                let names: Vec<String> = all_modifiers().iter().map(|m| m.name().to_string()).collect();
+                format!("Unknown mode '{}', use {} or none", name, names.join(", "))
+            })?;
+        stack.push(modifier);
+    }
+    conversation.session.mode = stack.mode();
+    save_session(&conversation.session);
+
+    println!(
+        "Mode: {}",
+        conversation.session.mode.as_deref().unwrap_or("none")
+    );
+    Ok(())
+}
+
+fn system(conversation: &mut Conversation, text: &str) -> CommandResult {
+    if text.is_empty() {
+        match &conversation.session.system {
+            Some(system) => println!("System prompt: {}", system),
+            None => println!("Using the provider's default system prompt."),
+        }
+        return Ok(());
+    }
+
+    conversation.session.system = Some(text.to_string());
+    save_session(&conversation.session);
+    println!("System prompt set.");
+    Ok(())
+}
+
+fn save(conversation: &mut Conversation, path: &str) -> CommandResult {
+    if let Some(argument) = path
+        .strip_prefix("code")
+        .filter(|rest| rest.is_empty() || rest.starts_with(' '))
+    {
+        return save_code(conversation, argument.trim());
+    }
+
+    if path.is_empty() {
+        store::save(&conversation.session)?;
+        println!("Saved session {}.", conversation.session.id);
+        return Ok(());
+    }
+
+    let rendered = if path.ends_with(".html") || path.ends_with(".htm") {
+        export::to_html(&conversation.session)
+    } else {
+        export::to_markdown(&conversation.session)
+    };
+    if Path::new(path).exists() && !ask_yes_no(&format!("{} exists, overwrite it?", path))? {
+        return Ok(());
+    }
+    fs::write(path, rendered)?;
+    println!("Exported session to {}.", path);
+    Ok(())
+}
+
+fn save_code(conversation: &mut Conversation, argument: &str) -> CommandResult {
+    let (number, path) = match argument.split_once(' ') {
+        Some((number, path)) => (number, Some(path.trim())),
+        None => (argument, None),
+    };
+    if number.is_empty() {
+        return Err("Usage: /save code N [path]".into());
+    }
+
+    let last = conversation
+        .last_response()
+        .ok_or("There is no response to save code from yet")?;
+    let block = code_block(&last, number)?;
+    let path = resolve_path(&block, path);
+    if path.exists() && !ask_yes_no(&format!("{} exists, overwrite it?", path.display()))? {
+        return Ok(());
+    }
+
+    fs::write(&path, &block.code)?;
+    println!(
+        "Saved code block {} to {} ({} lines).",
+        block.number,
+        path.display(),
+        block.code.lines().count()
+    );
+    Ok(())
+}
+
+fn load(conversation: &mut Conversation, id: &str) -> CommandResult {
+    if id.is_empty() {
+        return Err("Usage: /load <session-id>, see `history list` for ids".into());
+    }
+
+    conversation.session = store::load(id)?;
+    conversation.load_model()?;
+
+    let turns = conversation.session.path().len();
+    println!(
+        "Loaded {} ({} turns): {}",
+        conversation.session.id, turns, conversation.session.title
+    );
+    Ok(())
+}
+
+// There's no tokenizer for either provider here, ~4 characters per token is the usual rule of thumb for English
+fn tokens(conversation: &mut Conversation) -> CommandResult {
+    let path = conversation.session.path();
+    let characters: usize = path
+        .iter()
+        .map(|turn| turn.content.chars().count())
+        .sum::<usize>()
+        + conversation
+            .session
+            .system
+            .as_ref()
+            .map_or(0, |s| s.chars().count());
+
+    println!(
+        "About {} tokens across {} turns.",
+        characters.div_ceil(4),
+        path.len()
+    );
+    Ok(())
+}
+
+fn copy(conversation: &mut Conversation, argument: &str) -> CommandResult {
+    let last = conversation
+        .last_response()
+        .ok_or("There is no response to copy yet")?;
+
+    // `/copy code N` copies just the code of block N, without the banners and colors around it
+    if let Some(number) = argument.strip_prefix("code") {
+        let number = number.trim();
+        if number.is_empty() {
+            return Err("Usage: /copy code N".into());
+        }
+        let block = code_block(&last, number)?;
+        copy_to_clipboard(&block.code)?;
+        println!(
+            "Copied code block {} ({} lines).",
+            block.number,
+            block.code.lines().count()
+        );
+        return Ok(());
+    }
+    if !argument.is_empty() {
+        return Err("Usage: /copy or /copy code N".into());
+    }
+
+    copy_to_clipboard(&last)?;
+    println!(
+        "Copied the last response ({} characters).",
+        last.chars().count()
+    );
+    Ok(())
+}
+
+async fn steps(conversation: &mut Conversation) -> CommandResult {
+    let last = conversation
+        .last_response()
+        .ok_or("There is no response to run yet")?;
+
+    run_response_steps(conversation, &last).await
+}
+
+fn attach(conversation: &mut Conversation, paths: &str) -> CommandResult {
+    if paths.is_empty() {
+        let session = &conversation.session.attachments;
+        let pending = &conversation.pending_attachments;
+        if session.is_empty() && pending.is_empty() {
+            println!("No files attached.");
+        }
+        for path in session {
+            println!("  {}", path);
+        }
+        for path in pending {
+            println!(
+                "  {} {}",
+                path,
+                color_text("(sent with the next prompt)", "yellow")
+            );
+        }
+        return Ok(());
+    }
+
+    if paths == "clear" {
+        conversation.pending_attachments.clear();
+        conversation.session.attachments.clear();
+        save_session(&conversation.session);
+        println!("No files are attached now, /apply won't change any until you attach them again.");
+        return Ok(());
+    }
+
+    // Quoted paths can have spaces in them
+    for path in split_words(paths) {
+        let text =
+            fs::read_to_string(&path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+        if !conversation.pending_attachments.contains(&path) {
+            conversation.pending_attachments.push(path.clone());
+        }
+        println!(
+            "Attached {} ({} lines), it's sent with your next prompt.",
+            path,
+            text.lines().count()
+        );
+    }
+    Ok(())
+}
+
+fn apply(conversation: &mut Conversation, argument: &str) -> CommandResult {
+    match argument {
+        "" => {}
+        "undo" => return undo_apply(conversation),
+        _ => return Err("Usage: /apply or /apply undo".into()),
+    }
+
+    let last = conversation
+        .last_response()
+        .ok_or("There is no response to apply yet")?;
+    let edits = parse_edits(&last);
+    if edits.is_empty() {
+        return Err(
+            "No diff or search/replace blocks in the last response, ask with -d or -r to get one"
+                .into(),
+        );
+    }
+
+    // Preview everything first, files with conflicts are reported and left out
+    let mut ready = Vec::new();
+    for edit in &edits {
+        let planned = match plan(edit, &conversation.session.attachments) {
+            Ok(planned) => planned,
+            Err(e) => {
+                eprintln!("{}", format_error(&e));
+                continue;
+            }
+        };
+
+        if planned.conflicts.is_empty() {
+            print!(
+                "\n{}",
+                highlight_diff(language_for_path(&planned.path), &planned.preview)
+            );
+            ready.push(planned);
+        } else {
+            println!(
+                "\n{} {} is left unchanged:",
+                color_text("Conflict:", "red"),
+                planned.path
+            );
+            for conflict in &planned.conflicts {
+                println!("  {}", conflict);
+            }
+        }
+    }
+    if ready.is_empty() {
+        return Err("Nothing to apply".into());
+    }
+
+    let names: Vec<&str> = ready.iter().map(|planned| planned.path.as_str()).collect();
+    if !ask_yes_no(&format!("\nApply the changes to {}?", names.join(", ")))? {
+        return Ok(());
+    }
+
+    backup::save(&conversation.session.id, &ready)?;
+    for planned in &ready {
+        fs::write(&planned.path, &planned.updated)?;
+    }
+    println!(
+        "Updated {}. /apply undo puts the previous version back.",
+        names.join(", ")
+    );
+    Ok(())
+}
+
+fn undo_apply(conversation: &mut Conversation) -> CommandResult {
+    let backup =
+        backup::latest(&conversation.session.id).ok_or("There are no applied changes to undo")?;
+
+    let changed = backup.changed_since();
+    if !changed.is_empty()
+        && !ask_yes_no(&format!(
+            "{} changed after the edit was applied, undo anyway and lose those changes?",
+            changed.join(", ")
+        ))?
+    {
+        return Ok(());
+    }
+
+    let paths = backup.paths().join(", ");
+    backup.restore()?;
+    println!("Restored {}.", paths);
+    Ok(())
+}
+
+// `/edit` composes a new prompt, `/edit N` rewrites prompt N (inline, or in the editor when no text is given)
+async fn edit(conversation: &mut Conversation, argument: &str) -> Outcome {
+    let (first, rest) = argument.split_once(' ').unwrap_or((argument, ""));
+    if !first.is_empty() && first.chars().all(|c| c.is_ascii_digit()) {
+        if let Err(e) = edit_prompt(conversation, first, rest.trim()).await {
+            eprintln!("{}", format_error(&e.to_string()));
+        }
+        return Outcome::Continue;
+    }
+
+    match compose_in_editor(argument) {
+        Ok(text) if !text.trim().is_empty() => {
+            println!("{}", text.trim_end());
+            Outcome::Send(text.trim().to_string())
+        }
+        Ok(_) => {
+            println!("Empty prompt, nothing sent.");
+            Outcome::Continue
+        }
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format_error(&format!("Could not open the editor: {}", e))
+            );
+            Outcome::Continue
+        }
+    }
+}
+
+async fn edit_prompt(conversation: &mut Conversation, number: &str, text: &str) -> CommandResult {
+    let user_index = prompt_index(conversation, number)?;
+
+    let prompt = if text.is_empty() {
+        compose_in_editor(&conversation.session.turns[user_index].content)?
+    } else {
+        text.to_string()
+    };
+    if prompt.trim().is_empty() {
+        return Err("Empty prompt, nothing sent".into());
+    }
+
+    let response = conversation.edit_prompt(user_index, prompt.trim()).await?;
+    conversation.print_response(&response);
+    Ok(())
+}
+
+// Turns the N from /turns into the index of that prompt in the session
+fn prompt_index(conversation: &Conversation, number: &str) -> Result<usize, String> {
+    let prompts = conversation.session.prompt_indices();
+    number
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| prompts.get(i).copied())
+        .ok_or_else(|| {
+            format!(
+                "No prompt {}, see /turns for the numbers (1 to {})",
+                number,
+                prompts.len()
+            )
+        })
+}
+
+fn preview(text: &str) -> String {
+    let first_line = text.lines().next().unwrap_or("");
+    if first_line.chars().count() > 60 || text.lines().count() > 1 {
+        format!("{}...", first_line.chars().take(60).collect::<String>())
+    } else {
+        first_line.to_string()
+    }
+}
+
+fn unknown_command(name: &str) -> String {
+    let mut suggestions: Vec<(usize, &str)> = COMMANDS
+        .iter()
+        .map(|command| (edit_distance(name, command.name), command.name))
+        .filter(|(distance, command)| *distance <= 2 || command.starts_with(name))
+        .collect();
+    suggestions.sort();
+
+    match suggestions.first() {
+        Some((_, suggestion)) => {
+            format!("Unknown command /{}. Did you mean /{}?", name, suggestion)
+        }
+        None => format!(
+            "Unknown command /{}. Type /help to see the available commands.",
+            name
+        ),
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
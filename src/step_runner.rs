@@ -0,0 +1,175 @@
+// src/step_runner.rs
+
+// Walks through the steps of a command mode answer and runs the ones the user picks, so commands don't
+// have to be copied over by hand. Used by `--run` on the command line and `/steps` in a conversation.
+//
+// Each command runs in the user's own shell ($SHELL) with its output streamed as it happens. When a step
+// fails, its error output can be sent back to the model as the next turn of the conversation.
+
+use crate::conversation::Conversation;
+use crate::danger;
+use crate::process::run_teed;
+use crate::prompt::{ask_typed_yes, ask_yes_no};
+use crate::steps::{extract_steps, Step};
+use crate::text_formatter::{color_text, danger_badge, format_error};
+use rustyline::DefaultEditor;
+use std::env;
+use std::io::{self, Write};
+use std::process::{Command, ExitStatus};
+
+// Only the end of a long error output is sent to the model
+const STDERR_LINES: usize = 60;
+const MAX_STDERR_CHARS: usize = 4000;
+
+// A step that failed and what it printed to stderr
+struct Failure {
+    command: String,
+    status: ExitStatus,
+    stderr: String,
+}
+
+enum Choice {
+    Run,
+    Skip,
+    Edit,
+    Abort,
+}
+
+// Runs the steps found in `response`. If a step fails and the user asks for help, the error is sent
+// to the model and its reply printed.
+pub async fn run_response_steps(
+    conversation: &mut Conversation,
+    response: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let steps = extract_steps(response);
+    if steps.is_empty() {
+        return Err("No commands found in the response".into());
+    }
+
+    let Some(failure) = run_steps(&steps)? else {
+        return Ok(());
+    };
+
+    let prompt = failure_prompt(&failure);
+    let response = conversation.send(&prompt).await?;
+    conversation.print_response(&response);
+    Ok(())
+}
+
+// Returns the failure the user chose to send back to the model, if any
+fn run_steps(steps: &[Step]) -> Result<Option<Failure>, Box<dyn std::error::Error>> {
+    let shell = env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+
+    for step in steps {
+        println!();
+        println!(
+            "{} {}",
+            color_text(&format!("Step {}/{}:", step.number, steps.len()), "green"),
+            step.description
+        );
+
+        let mut command = step.command.clone();
+        loop {
+            println!("  {}", color_text(&command, "cyan"));
+
+            // Risky commands are explained and need a typed "yes" instead of a single key
+            let explanations = danger::check(&command);
+            for explanation in &explanations {
+                println!("  {} {}", danger_badge(), explanation);
+            }
+
+            match ask_choice()? {
+                Choice::Run
+                    if !explanations.is_empty()
+                        && !ask_typed_yes("  Type yes to run it anyway:")? =>
+                {
+                    continue
+                }
+                Choice::Run => break,
+                Choice::Skip => {
+                    command.clear();
+                    break;
+                }
+                Choice::Edit => command = edit_command(&command)?,
+                Choice::Abort => {
+                    println!("Stopped before step {}.", step.number);
+                    return Ok(None);
+                }
+            }
+        }
+
+        if command.trim().is_empty() {
+            continue;
+        }
+
+        // Stdout stays on the terminal, stderr is shown and kept for the model
+        let captured = run_teed(
+            Command::new(&shell).arg("-c").arg(&command),
+            false,
+            STDERR_LINES,
+        )?;
+        if captured.status.success() {
+            continue;
+        }
+        let (status, stderr) = (captured.status, captured.stderr);
+
+        eprintln!(
+            "{}",
+            format_error(&format!("Step {} failed ({})", step.number, status))
+        );
+        if ask_yes_no("Ask the model about the error?")? {
+            return Ok(Some(Failure {
+                command,
+                status,
+                stderr,
+            }));
+        }
+        if !ask_yes_no("Continue with the next step?")? {
+            return Ok(None);
+        }
+    }
+
+    println!("\nAll steps done.");
+    Ok(None)
+}
+
+fn ask_choice() -> io::Result<Choice> {
+    loop {
+        print!("  [r]un, [s]kip, [e]dit or [a]bort? ");
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input)? == 0 {
+            return Ok(Choice::Abort);
+        }
+
+        match input.trim().to_lowercase().as_str() {
+            "r" | "run" | "y" => return Ok(Choice::Run),
+            "s" | "skip" | "n" => return Ok(Choice::Skip),
+            "e" | "edit" => return Ok(Choice::Edit),
+            "a" | "abort" | "q" => return Ok(Choice::Abort),
+            _ => continue,
+        }
+    }
+}
+
+// Edits the command in place on a single line, Ctrl-C keeps it as it was
+fn edit_command(command: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let mut editor = DefaultEditor::new()?;
+    match editor.readline_with_initial("  $ ", (command, "")) {
+        Ok(line) => Ok(line),
+        Err(rustyline::error::ReadlineError::Interrupted) => Ok(command.to_string()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn failure_prompt(failure: &Failure) -> String {
+    let stderr = failure.stderr.trim();
+    let skip = stderr.chars().count().saturating_sub(MAX_STDERR_CHARS);
+    let stderr: String = stderr.chars().skip(skip).collect();
+
+    format!(
+        "Running `{}` failed ({}). This is what it printed to stderr:\n\n```\n{}\n```\n\nWhat went wrong and how do I fix it?",
+        failure.command, failure.status, stderr
+    )
+}
@@ -0,0 +1,238 @@
+// src/steps.rs
+
+// Pulls the steps out of a command mode response so they can be saved as a script or handed to other tools.
+//
+// A step is a numbered list item. Its command is the fenced shell block inside the item if there is one,
+// otherwise the first inline code span in the item's text. Fenced shell blocks outside any list become
+// steps of their own. Items without a command, like "Open a terminal", are left out.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+
+lazy_static! {
+    static ref LIST_ITEM: Regex = Regex::new(r"^\s*(\d+)[.)]\s+(.*)$").unwrap();
+    static ref INLINE_CODE: Regex = Regex::new(r"`([^`]+)`").unwrap();
+}
+
+// Fence languages that hold commands to type, an unlabeled fence counts too
+const SHELL_LANGUAGES: [&str; 6] = ["", "bash", "sh", "shell", "zsh", "console"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Step {
+    pub number: usize,
+    pub description: String,
+    pub command: String,
+}
+
+// The text of one list item with the fenced shell blocks found under it
+struct Item {
+    text: String,
+    blocks: Vec<String>,
+}
+
+pub fn extract_steps(response: &str) -> Vec<Step> {
+    let mut items: Vec<Item> = Vec::new();
+    let mut in_item = false;
+    let mut fence: Option<(bool, Vec<&str>)> = None;
+
+    for line in response.lines() {
+        let trimmed = line.trim_start();
+
+        if let Some((is_shell, lines)) = fence.as_mut() {
+            if trimmed.starts_with("```") {
+                let block = command_text(lines);
+                if *is_shell && !block.is_empty() {
+                    // A block outside a list is a step without a description
+                    if !in_item {
+                        items.push(Item {
+                            text: String::new(),
+                            blocks: Vec::new(),
+                        });
+                    }
+                    if let Some(item) = items.last_mut() {
+                        item.blocks.push(block);
+                    }
+                }
+                fence = None;
+            } else {
+                lines.push(line);
+            }
+            continue;
+        }
+
+        if let Some(language) = trimmed.strip_prefix("```") {
+            let language = language.trim().to_lowercase();
+            // A block right under an item belongs to it, indented or not
+            fence = Some((SHELL_LANGUAGES.contains(&language.as_str()), Vec::new()));
+        } else if let Some(caps) = LIST_ITEM.captures(line) {
+            items.push(Item {
+                text: caps[2].trim().to_string(),
+                blocks: Vec::new(),
+            });
+            in_item = true;
+        } else if in_item && line.starts_with([' ', '\t']) && !trimmed.is_empty() {
+            // Wrapped item text
+            if let Some(item) = items.last_mut() {
+                item.text.push(' ');
+                item.text.push_str(trimmed);
+            }
+        } else if !trimmed.is_empty() {
+            in_item = false;
+        }
+    }
+
+    items
+        .into_iter()
+        .filter_map(|item| {
+            let command = if item.blocks.is_empty() {
+                INLINE_CODE.captures(&item.text)?[1].trim().to_string()
+            } else {
+                item.blocks.join("\n")
+            };
+            Some((describe(&item.text, item.blocks.is_empty()), command))
+        })
+        .enumerate()
+        .map(|(i, (description, command))| Step {
+            number: i + 1,
+            description,
+            command,
+        })
+        .collect()
+}
+
+// The item text without the command it contains, e.g. "Initialize the repository: `git init`" -> "Initialize the repository"
+fn describe(text: &str, command_is_inline: bool) -> String {
+    let text = if command_is_inline {
+        INLINE_CODE.replacen(text, 1, "").to_string()
+    } else {
+        text.to_string()
+    };
+
+    text.replace("**", "")
+        .trim()
+        .trim_end_matches([':', ',', '-'])
+        .trim()
+        .to_string()
+}
+
+// Block lines without their shared indentation or a "$ " prompt in front of them
+fn command_text(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .map(|line| line.strip_prefix("$ ").unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+// A bash script running the steps in order, stopping at the first failure
+pub fn to_script(steps: &[Step], prompt: &str) -> String {
+    let mut script = String::from("#!/usr/bin/env bash\n");
+    for line in prompt.lines() {
+        script.push_str(&format!("# {}\n", line));
+    }
+    script.push_str("# Generated by console_ai_chat, read it before running it.\n");
+    script.push_str("set -euo pipefail\n");
+
+    for step in steps {
+        script.push('\n');
+        let description = if step.description.is_empty() {
+            format!("Step {}", step.number)
+        } else {
+            step.description.clone()
+        };
+        for (i, line) in description.lines().enumerate() {
+            match i {
+                0 => script.push_str(&format!("# {}. {}\n", step.number, line)),
+                _ => script.push_str(&format!("#    {}\n", line)),
+            }
+        }
+        script.push_str(&step.command);
+        script.push('\n');
+    }
+
+    script
+}
+
+pub fn to_json(steps: &[Step]) -> String {
+    serde_json::to_string_pretty(steps).unwrap_or_else(|_| "[]".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commands(steps: &[Step]) -> Vec<&str> {
+        steps.iter().map(|step| step.command.as_str()).collect()
+    }
+
+    #[test]
+    fn takes_inline_code_as_the_command() {
+        let steps = extract_steps(
+            "1. Open a terminal\n\
+             2. **Initialize** the repository: `git init`\n\
+             3. Stage everything,\n   \
+             including new files: `git add .`",
+        );
+
+        assert_eq!(commands(&steps), vec!["git init", "git add ."]);
+        assert_eq!(steps[0].number, 1);
+        assert_eq!(steps[0].description, "Initialize the repository");
+        // Wrapped item text is joined
+        assert_eq!(
+            steps[1].description,
+            "Stage everything, including new files"
+        );
+    }
+
+    #[test]
+    fn prefers_fenced_shell_blocks_and_strips_prompts() {
+        let steps = extract_steps(
+            "1. Install it:\n   \
+             ```bash\n   \
+             $ sudo apt update\n   \
+             $ sudo apt install `nginx`\n   \
+             ```\n\
+             2. Edit the config:\n\
+             ```nginx\n\
+             server {}\n\
+             ```",
+        );
+
+        assert_eq!(
+            commands(&steps),
+            vec!["sudo apt update\nsudo apt install `nginx`"]
+        );
+        assert_eq!(steps[0].description, "Install it");
+    }
+
+    #[test]
+    fn blocks_outside_a_list_are_steps_of_their_own() {
+        let steps = extract_steps("Run:\n\n```sh\nmake\n```\n\n1. Then `make install`");
+
+        assert_eq!(commands(&steps), vec!["make", "make install"]);
+        assert_eq!(steps[0].description, "");
+        assert_eq!(steps[1].number, 2);
+    }
+
+    #[test]
+    fn scripts_stop_at_the_first_failure() {
+        let steps = extract_steps("1. Build: `make`\n2. `make test`");
+        let script = to_script(&steps, "build it");
+
+        assert!(script.starts_with("#!/usr/bin/env bash\n# build it\n"));
+        assert!(script.contains("set -euo pipefail\n"));
+        assert!(script.contains("# 1. Build\nmake\n"));
+        assert!(script.contains("# 2. Step 2\nmake test\n"));
+    }
+}
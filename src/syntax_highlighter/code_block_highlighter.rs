@@ -1,5 +1,6 @@
 // src/syntax_highlighter/code_block_highlighter.rs
 
+use super::diff_highlighter::highlight_diff;
 use super::token_highlighter::highlight_code;
 
 pub fn highlight_code_blocks(content: &str) -> String {
@@ -7,12 +8,14 @@ pub fn highlight_code_blocks(content: &str) -> String {
     let mut in_code_block = false;
     let mut language_name = String::new();
     let mut code_block = String::new();
+    // Blocks are numbered so they can be picked with /run N and /save code N
+    let mut block_number = 0;
 
     for line in content.lines() {
         if line.starts_with("```") {
             if in_code_block {
                 // End of code block
-                highlighted_content.push_str(&highlight_code(&language_name, &code_block));
+                highlighted_content.push_str(&highlight_block(&language_name, &code_block));
 
                 highlighted_content.push_str("\x1b[32m");
                 highlighted_content.push_str("\n ---- END OF CODE BLOCK ---- \n");
@@ -23,8 +26,13 @@ pub fn highlight_code_blocks(content: &str) -> String {
             } else {
                 // Start of code block
                 language_name = line.trim_start_matches("```").to_string();
+                block_number += 1;
+                let label = match language_name.trim() {
+                    "" => block_number.to_string(),
+                    language => format!("{} ({})", block_number, language),
+                };
                 highlighted_content.push_str("\x1b[32m");
-                highlighted_content.push_str("\n ---- START OF CODE BLOCK ---- \n");
+                highlighted_content.push_str(&format!("\n ---- START OF CODE BLOCK {} ---- \n", label));
                 highlighted_content.push_str("\x1b[0m");
                 in_code_block = true;
             }
@@ -41,12 +49,20 @@ pub fn highlight_code_blocks(content: &str) -> String {
 
     if in_code_block {
         // Handle unclosed code block
-        highlighted_content.push_str(&highlight_code(&language_name, &code_block));
+        highlighted_content.push_str(&highlight_block(&language_name, &code_block));
     }
 
     highlighted_content
 }
 
+// Diffs get their added and removed lines colored instead of being tokenized
+fn highlight_block(language_name: &str, code: &str) -> String {
+    match language_name.trim() {
+        "diff" | "patch" => highlight_diff("", code),
+        language => highlight_code(language, code),
+    }
+}
+
 fn format_explanatory_line(line: &str) -> String {
     let mut formatted = String::new();
     let mut in_backticks = false;
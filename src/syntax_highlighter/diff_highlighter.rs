@@ -0,0 +1,53 @@
+// src/syntax_highlighter/diff_highlighter.rs
+
+use super::language_factory::language_factory::get_language;
+use super::token_highlighter::highlight_code;
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const CYAN: &str = "\x1b[36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+// Colors a unified diff: file headers in bold, hunk headers in cyan, removed lines in red.
+// Added and unchanged lines keep the language's syntax highlighting behind a green or blank marker.
+// Without a known language, added lines are plain green.
+pub fn highlight_diff(language_name: &str, diff: &str) -> String {
+    let known_language = get_language(language_name).is_some();
+    let mut highlighted = String::new();
+
+    for line in diff.lines() {
+        if line.starts_with("+++ ") || line.starts_with("--- ") || line.starts_with("diff ") {
+            highlighted.push_str(&format!("{}{}{}", BOLD, line, RESET));
+        } else if line.starts_with("@@") {
+            highlighted.push_str(&format!("{}{}{}", CYAN, line, RESET));
+        } else if let Some(code) = line.strip_prefix('+') {
+            if known_language {
+                highlighted.push_str(&format!(
+                    "{}+{}{}",
+                    GREEN,
+                    RESET,
+                    highlight_line(language_name, code)
+                ));
+            } else {
+                highlighted.push_str(&format!("{}{}{}", GREEN, line, RESET));
+            }
+        } else if line.starts_with('-') {
+            highlighted.push_str(&format!("{}{}{}", RED, line, RESET));
+        } else if let Some(code) = line.strip_prefix(' ') {
+            highlighted.push(' ');
+            highlighted.push_str(&highlight_line(language_name, code));
+        } else {
+            highlighted.push_str(line);
+        }
+        highlighted.push('\n');
+    }
+
+    highlighted
+}
+
+fn highlight_line(language_name: &str, code: &str) -> String {
+    highlight_code(language_name, code)
+        .trim_end_matches('\n')
+        .to_string()
+}
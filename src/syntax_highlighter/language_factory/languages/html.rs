@@ -2,6 +2,7 @@ use crate::syntax_highlighter::language_factory::language_def::LanguageDef;
 use lazy_static::lazy_static;
 use std::collections::HashSet;
 
+#[allow(clippy::upper_case_acronyms)]
 pub struct HTML;
 
 lazy_static! {
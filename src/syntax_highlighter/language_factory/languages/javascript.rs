@@ -13,7 +13,7 @@ lazy_static! {
             "catch", "throw", "new", "typeof", "instanceof", "in", "of", "class",
             "extends", "super", "this", "import", "export", "default", "finally",
         ];
-        keywords.iter().cloned().collect()
+        keywords.into_iter().collect()
     };
     
     // prettier-ignore
@@ -23,7 +23,7 @@ lazy_static! {
             "||", "!", "?", ":", ".", "=>", "++", "--", "&", "|", "^", "~", "<<", ">>", ">>>",
             "+=", "-=", "*=", "/=", "%=", "<<=", ">>=", ">>>=", "&=", "|=", "^=",
         ];
-        operators.iter().cloned().collect()
+        operators.into_iter().collect()
     };
     
     // prettier-ignore
@@ -34,17 +34,17 @@ lazy_static! {
             "fetch", "log", "error", "warn", "info", "debug", "dir", "dirxml", "table", "trace",
             "group", "signal", "abort", "race", "all", "resolve", "reject", "json"
         ];
-        built_ins.iter().cloned().collect()
+        built_ins.into_iter().collect()
     };
     
     static ref LITERALS: HashSet<&'static str> = {
         let literals = vec!["true", "false", "null", "undefined"];
-        literals.iter().cloned().collect()
+        literals.into_iter().collect()
     };
     
     static ref TYPES: HashSet<&'static str> = {
         let types = vec!["number", "string", "boolean", "object", "symbol", "bigint"];
-        types.iter().cloned().collect()
+        types.into_iter().collect()
     };
     
     // prettier-ignore
@@ -52,23 +52,23 @@ lazy_static! {
         let modifiers = vec![
             "public", "private", "protected", "static", "readonly", "const",
         ];
-        modifiers.iter().cloned().collect()
+        modifiers.into_iter().collect()
     };
     
     static ref ANNOTATIONS: HashSet<&'static str> = {
         let annotations = vec!["@deprecated", "@override"];
-        annotations.iter().cloned().collect()
+        annotations.into_iter().collect()
     };
     
     static ref PREPROCESSOR_DIRECTIVES: HashSet<&'static str> = {
         let preprocessor_directives = vec![];
-        preprocessor_directives.iter().cloned().collect()
+        preprocessor_directives.into_iter().collect()
     };
     
     // unused for now but will be used for multi-line comments
     // static ref COMMENTS: HashSet<&'static str> = {
     //     let comments = vec!["//", "/*", "*/"];
-    //     comments.iter().cloned().collect()
+    //     comments.into_iter().collect()
     // };
 }
 
@@ -1,5 +1,6 @@
 // src/syntax_highligher/language_factory/mod.rs
 
 pub mod language_def;
+#[allow(clippy::module_inception)]
 pub mod language_factory;
 pub mod languages;
@@ -2,6 +2,9 @@
 
 pub mod language_factory;
 mod code_block_highlighter;
+mod diff_highlighter;
 mod token_highlighter;
 
-pub use code_block_highlighter::highlight_code_blocks;
\ No newline at end of file
+pub use code_block_highlighter::highlight_code_blocks;
+pub use diff_highlighter::highlight_diff;
+pub use token_highlighter::{escape_html, highlight_code, highlight_code_html};
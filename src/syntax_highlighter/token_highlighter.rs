@@ -4,10 +4,28 @@ use std::collections::HashSet;
 use crate::syntax_highlighter::language_factory::language_def::LanguageDef;
 use crate::syntax_highlighter::language_factory::language_factory::get_language;
 
+// Tokens can be colored with ANSI escapes for the terminal, or wrapped in CSS classes for HTML exports
+#[derive(Clone, Copy, PartialEq)]
+enum Output {
+    Ansi,
+    Html,
+}
+
 pub fn highlight_code(language_name: &str, code: &str) -> String {
+    highlight_code_as(language_name, code, Output::Ansi)
+}
+
+// Same tokenization as highlight_code, but emits `<span class="tok-...">` elements with HTML-escaped text
+pub fn highlight_code_html(language_name: &str, code: &str) -> String {
+    highlight_code_as(language_name, code, Output::Html)
+}
+
+fn highlight_code_as(language_name: &str, code: &str, output: Output) -> String {
+    // Fences without a language, or with one we don't have a definition for, are printed as-is
     let language = match get_language(language_name) {
         Some(lang) => lang,
-        None => panic!("Language not found"),
+        None if output == Output::Html => return escape_html(code),
+        None => return code.to_string(),
     };
 
     let mut highlighted_code = String::new();
@@ -21,12 +39,12 @@ pub fn highlight_code(language_name: &str, code: &str) -> String {
         // If there's a comment, highlight the code part and the comment part separately. Ensures that comments are colored appropriately
         if let Some(index) = comment_start {
             let (code_part, comment_part) = line.split_at(index);
-            highlighted_code.push_str(&highlight_code_part(language_name, code_part, &language));
-            highlighted_code.push_str(&color_token(comment_part, "comment"));
+            highlighted_code.push_str(&highlight_code_part(language_name, code_part, language.as_ref(), output));
+            highlighted_code.push_str(&color_token(comment_part, "comment", output));
             highlighted_code.push('\n');
         } else {
             // If there's no comment, highlight the whole line
-            highlighted_code.push_str(&highlight_code_part(language_name, line, &language));
+            highlighted_code.push_str(&highlight_code_part(language_name, line, language.as_ref(), output));
             highlighted_code.push('\n');
         }
     }
@@ -38,7 +56,8 @@ pub fn highlight_code(language_name: &str, code: &str) -> String {
 fn highlight_code_part(
     language_name: &str,
     code_part: &str,
-    language: &Box<dyn LanguageDef>,
+    language: &dyn LanguageDef,
+    output: Output,
 ) -> String {
     let mut colored_line = String::new();
     let mut in_string = false;
@@ -51,7 +70,7 @@ fn highlight_code_part(
             current_token.push(ch);
 
             if ch == string_start {
-                colored_line.push_str(&color_token(&current_token, "string"));
+                colored_line.push_str(&color_token(&current_token, "string", output));
                 current_token.clear();
                 in_string = false;
             }
@@ -71,6 +90,7 @@ fn highlight_code_part(
                         language.annotations(),
                         language.preprocessor_directives(),
                     ),
+                    output,
                 ));
                 current_token.clear();
             }
@@ -99,6 +119,7 @@ fn highlight_code_part(
                         language.annotations(),
                         language.preprocessor_directives(),
                     ),
+                    output,
                 ));
                 current_token.clear();
             }
@@ -120,6 +141,7 @@ fn highlight_code_part(
                         language.annotations(),
                         language.preprocessor_directives(),
                     ),
+                    output,
                 ));
             }
         }
@@ -140,12 +162,14 @@ fn highlight_code_part(
                 language.annotations(),
                 language.preprocessor_directives(),
             ),
+            output,
         ));
     }
 
     colored_line
 }
 
+#[allow(clippy::too_many_arguments)]
 fn categorize_token(
     token: &str,
     keywords: &HashSet<&str>,
@@ -187,7 +211,7 @@ fn categorize_token(
     } else if token
         .chars()
         .next()
-        .map_or(false, |c| c.is_alphabetic() || c == '_' || c == '$')
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
     {
         if token.chars().next().unwrap().is_uppercase() {
             "method_function".to_string()
@@ -200,7 +224,15 @@ fn categorize_token(
     }
 }
 
-fn color_token(token: &str, category: &str) -> String {
+fn color_token(token: &str, category: &str, output: Output) -> String {
+    if output == Output::Html {
+        return format!(
+            "<span class=\"tok-{}\">{}</span>",
+            category.replace('_', "-"),
+            escape_html(token)
+        );
+    }
+
     let color_code = match category {
         "comment" => "\x1b[38;2;121;121;121m",         // #797979
         "variable" => "\x1b[38;2;214;214;214m",        // #d6d6d6
@@ -217,4 +249,11 @@ fn color_token(token: &str, category: &str) -> String {
         _ => "\x1b[0m",                                // Default (Reset)
     };
     format!("{}{}\x1b[0m", color_code, token)
-}
\ No newline at end of file
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
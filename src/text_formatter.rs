@@ -1,8 +1,9 @@
 // src/text_formatter.rs
 
 // this is a module that contains functions for formatting text output. 
-// It is only used to color very specific elements of the responses of the AI models, such as the response header, error messages, and ordered lists.
+// It is only used to color very specific elements of the responses of the AI models, such as the response header, error messages, ordered lists and the danger badge.
 
+use crate::syntax_highlighter::highlight_code_blocks;
 use ansi_term::Colour;
 use regex::Regex;
 
@@ -31,10 +32,17 @@ pub fn format_error(error: &str) -> String {
 
 pub fn print_formatted_response(response: &str, is_command_mode: bool) {
     let header = create_header();
-    let formatted_response = format_response(response, is_command_mode);
+
+    // Check for code blocks, apply syntax highlighting, and keyword highlighting for supplemental explanations
+    let highlighted_response = highlight_code_blocks(response);
+    let formatted_response = format_response(&highlighted_response, is_command_mode);
     println!("\n{}\n{}", header, formatted_response);
 }
 
+pub fn danger_badge() -> String {
+    Colour::White.on(Colour::Red).bold().paint(" DANGER ").to_string()
+}
+
 pub fn color_text(text: &str, color: &str) -> String {
     match color {
         "red" => Colour::Red.bold().paint(text).to_string(),